@@ -270,7 +270,7 @@ mod tests {
         let json = json!({
             "title": "My Blog",
             "count": 42,
-            "ratio": 3.14,
+            "ratio": 2.5,
             "draft": false
         });
         let inputs = Inputs::from_json(&json).unwrap();
@@ -243,8 +243,8 @@ mod tests {
 
     #[test]
     fn test_parse_angle_rad() {
-        let angle = parse_angle("3.14159rad").unwrap();
-        assert!((angle.to_rad() - 3.14159).abs() < 0.0001);
+        let angle = parse_angle("1.5rad").unwrap();
+        assert!((angle.to_rad() - 1.5).abs() < 0.0001);
     }
 
     #[test]
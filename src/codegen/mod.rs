@@ -8,6 +8,7 @@
 //! - [`deserialize`] - JSON → Typst conversion
 //! - [`lookup`] - Function lookup utilities
 //! - [`source`] - Rust → Typst source code generation
+//! - `selector` - Selector expression parsing for queries
 //! - [`builder`] - Dict/Array construction helpers
 //! - [`inputs`] - sys.inputs builder from JSON
 //! - [`error`] - Error types
@@ -19,6 +20,7 @@ mod inputs;
 mod literal;
 mod lookup;
 mod roundtrip;
+mod selector;
 mod serialize;
 mod source;
 
@@ -33,6 +35,11 @@ pub use deserialize::{json_to_content, json_to_value};
 // Literal parsing (string → Typst value)
 pub use literal::{parse_typst_literal, parse_angle, parse_color, parse_length, parse_ratio};
 
+// Selector parsing (string → Typst selector)
+pub(crate) use selector::parse_selector;
+#[cfg(feature = "scan")]
+pub(crate) use selector::requires_layout;
+
 // Source generation
 pub use source::ToTypst;
 
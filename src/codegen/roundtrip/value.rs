@@ -25,9 +25,9 @@ fn primitives() {
         assert!(matches!(v, Value::Int(42)));
 
         // Float
-        let v = json_to_value(engine, context, library, &json!(3.14)).unwrap();
+        let v = json_to_value(engine, context, library, &json!(2.5)).unwrap();
         if let Value::Float(f) = v {
-            assert!((f - 3.14).abs() < 0.001);
+            assert!((f - 2.5).abs() < 0.001);
         } else {
             panic!("Expected Float");
         }
@@ -0,0 +1,151 @@
+//! Typst selector parsing from strings.
+//!
+//! Selector expressions (`heading.where(level: 2)`, `figure`, `<label>`,
+//! `selector(heading).or(figure)`) are evaluated as Typst code with the
+//! document's standard library, the same way `typst query` does. The
+//! expression cannot read files.
+//!
+//! Used by [`HtmlDocument::query`](crate::html::HtmlDocument::query) and
+//! [`ScanResult::query`](crate::process::ScanResult::query).
+
+use std::sync::LazyLock;
+
+use typst::comemo::Track;
+use typst::diag::{FileError, FileResult};
+use typst::engine::Sink;
+use typst::foundations::{Bytes, Datetime, FromValue, LocatableSelector, Scope, Selector};
+use typst::syntax::{FileId, Source, Span, SyntaxMode, VirtualPath};
+use typst::text::{Font, FontBook};
+use typst::utils::LazyHash;
+use typst::{Library, World};
+
+use crate::diagnostic::CompileError;
+use crate::world::{empty_fontbook, LibraryStrategy};
+
+/// Parse a selector expression with the given library.
+///
+/// Returns [`CompileError::Query`] if the expression fails to evaluate
+/// or is not a locatable selector.
+///
+/// # Example
+///
+/// ```ignore
+/// let selector = parse_selector("heading.where(level: 2)", &LibraryStrategy::Global)?;
+/// ```
+pub(crate) fn parse_selector(expr: &str, library: &LibraryStrategy) -> Result<Selector, CompileError> {
+    let world = SelectorWorld(library.library());
+
+    let mut sink = Sink::new();
+    let value = (typst::ROUTINES.eval_string)(
+        &typst::ROUTINES,
+        (&world as &dyn World).track(),
+        sink.track_mut(),
+        expr,
+        Span::detached(),
+        SyntaxMode::Code,
+        Scope::default(),
+    )
+    .map_err(|errors| {
+        let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+        CompileError::query(messages.join("; "))
+    })?;
+
+    LocatableSelector::from_value(value)
+        .map(|s| s.0)
+        .map_err(|e| CompileError::query(e.message().as_str()))
+}
+
+/// File id of the (empty) main file; it cannot be resolved to a path.
+static QUERY_ID: LazyLock<FileId> = LazyLock::new(|| FileId::new_fake(VirtualPath::new("query.typ")));
+
+/// World for evaluating selectors: a library, no fonts and no files.
+struct SelectorWorld<'a>(&'a LazyHash<Library>);
+
+impl World for SelectorWorld<'_> {
+    fn library(&self) -> &LazyHash<Library> {
+        self.0
+    }
+
+    fn book(&self) -> &LazyHash<FontBook> {
+        empty_fontbook()
+    }
+
+    fn main(&self) -> FileId {
+        *QUERY_ID
+    }
+
+    fn source(&self, _: FileId) -> FileResult<Source> {
+        Err(FileError::AccessDenied)
+    }
+
+    fn file(&self, _: FileId) -> FileResult<Bytes> {
+        Err(FileError::AccessDenied)
+    }
+
+    fn font(&self, _: usize) -> Option<Font> {
+        None
+    }
+
+    fn today(&self, _: Option<i64>) -> Option<Datetime> {
+        None
+    }
+}
+
+/// Name the part of a selector that needs layout (introspection), if any.
+///
+/// Text/regex matching and `before`/`after`/location selectors only work
+/// against a laid out document's introspector.
+#[cfg(feature = "scan")]
+pub(crate) fn requires_layout(selector: &Selector) -> Option<&'static str> {
+    match selector {
+        Selector::Elem(..) | Selector::Label(_) | Selector::Can(_) => None,
+        Selector::Or(selectors) | Selector::And(selectors) => {
+            selectors.iter().find_map(requires_layout)
+        }
+        Selector::Location(_) => Some("location"),
+        Selector::Regex(_) => Some("text/regex"),
+        Selector::Before { .. } => Some("before"),
+        Selector::After { .. } => Some("after"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(expr: &str) -> Result<Selector, CompileError> {
+        parse_selector(expr, &LibraryStrategy::Global)
+    }
+
+    #[test]
+    fn test_parse_selector() {
+        assert!(matches!(parse("heading").unwrap(), Selector::Elem(..)));
+        assert!(matches!(parse("<intro>").unwrap(), Selector::Label(_)));
+        assert!(parse("heading.where(").is_err());
+        assert!(parse("1 + 1").is_err());
+    }
+
+    #[test]
+    fn test_parse_selector_no_file_access() {
+        assert!(parse(r#"heading.where(body: read("Cargo.toml"))"#).is_err());
+        assert!(parse(r#"{ import "Cargo.toml"; heading }"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_selector_library() {
+        let expr = "heading.where(level: int(sys.inputs.level))";
+        assert!(parse(expr).is_err());
+
+        let mut inputs = typst::foundations::Dict::new();
+        inputs.insert("level".into(), typst::foundations::IntoValue::into_value("2"));
+        assert!(parse_selector(expr, &LibraryStrategy::with_inputs(inputs)).is_ok());
+    }
+
+    #[cfg(feature = "scan")]
+    #[test]
+    fn test_requires_layout() {
+        assert_eq!(requires_layout(&parse("selector(heading).or(<x>)").unwrap()), None);
+        let after = parse("selector(heading).after(<x>)").unwrap();
+        assert_eq!(requires_layout(&after), Some("after"));
+    }
+}
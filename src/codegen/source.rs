@@ -101,7 +101,7 @@ mod tests {
     #[test]
     fn test_numbers() {
         assert_eq!(42i64.to_typst(), "42");
-        assert_eq!(3.14f64.to_typst(), "3.14");
+        assert_eq!(2.5f64.to_typst(), "2.5");
     }
 
     #[test]
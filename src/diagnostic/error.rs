@@ -45,6 +45,42 @@ pub enum CompileError {
         message: String,
     },
 
+    /// PDF export failed.
+    #[error("PDF export failed: {message}")]
+    PdfExport {
+        /// Error message from typst_pdf.
+        message: String,
+    },
+
+    /// Image (raster) export failed.
+    #[error("image export failed: {message}")]
+    ImageExport {
+        /// Error message from the image encoder.
+        message: String,
+    },
+
+    /// Selector query failed (invalid selector expression).
+    #[error("query failed: {message}")]
+    Query {
+        /// Error message from evaluating the selector.
+        message: String,
+    },
+
+    /// Compilation was cancelled via a [`CancellationToken`](crate::world::CancellationToken).
+    #[error("compilation cancelled")]
+    Cancelled,
+
+    /// Compilation exceeded its wall-clock timeout.
+    #[error("compilation timed out after {timeout:?}")]
+    TimedOut {
+        /// The configured timeout.
+        timeout: std::time::Duration,
+    },
+
+    /// File was not compiled because the batch's error budget was exhausted.
+    #[error("skipped after earlier errors")]
+    Skipped,
+
     /// File I/O error.
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
@@ -77,6 +113,27 @@ impl CompileError {
         }
     }
 
+    /// Create a PDF export error.
+    pub fn pdf_export(message: impl Into<String>) -> Self {
+        Self::PdfExport {
+            message: message.into(),
+        }
+    }
+
+    /// Create an image export error.
+    pub fn image_export(message: impl Into<String>) -> Self {
+        Self::ImageExport {
+            message: message.into(),
+        }
+    }
+
+    /// Create a query error.
+    pub fn query(message: impl Into<String>) -> Self {
+        Self::Query {
+            message: message.into(),
+        }
+    }
+
     /// Check if this error contains any fatal errors (vs just warnings).
     pub fn has_fatal_errors(&self) -> bool {
         match self {
@@ -85,6 +142,16 @@ impl CompileError {
         }
     }
 
+    /// Check if this error was caused by cancellation or timeout.
+    pub fn is_interrupted(&self) -> bool {
+        matches!(self, Self::Cancelled | Self::TimedOut { .. })
+    }
+
+    /// Check if this file was skipped by a batch failure policy.
+    pub fn is_skipped(&self) -> bool {
+        matches!(self, Self::Skipped)
+    }
+
     /// Get the diagnostics if this is a compilation error.
     pub fn diagnostics(&self) -> Option<&Diagnostics> {
         match self {
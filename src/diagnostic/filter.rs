@@ -144,7 +144,7 @@ mod tests {
 
     #[test]
     fn test_filter_all_warnings() {
-        let diags = vec![
+        let diags = [
             SourceDiagnostic::error(Span::detached(), "error 1"),
             SourceDiagnostic::warning(Span::detached(), "warning 1"),
             SourceDiagnostic::warning(Span::detached(), "warning 2"),
@@ -158,7 +158,7 @@ mod tests {
 
     #[test]
     fn test_filter_message_contains() {
-        let diags = vec![
+        let diags = [
             SourceDiagnostic::error(Span::detached(), "error with keyword"),
             SourceDiagnostic::error(Span::detached(), "other error"),
         ];
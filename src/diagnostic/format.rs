@@ -3,7 +3,7 @@
 use std::fmt::Write;
 
 use typst::diag::{Severity, SourceDiagnostic};
-use typst::syntax::Span;
+use typst::syntax::{Source, Span};
 use typst::World;
 
 use super::info::{DiagnosticInfo, SourceLine, TraceInfo};
@@ -232,6 +232,20 @@ impl SpanLocation {
     pub fn from_span_with_offset<W: World>(world: &W, span: Span, main_line_offset: usize) -> Option<Self> {
         let id = span.id()?;
         let source = world.source(id).ok()?;
+        Self::from_source_with_offset(&source, span, id == world.main(), main_line_offset)
+    }
+
+    /// Resolve a span within an already loaded source.
+    ///
+    /// `is_main` tells whether `source` is the main file, whose line numbers
+    /// are corrected by `main_line_offset`.
+    pub fn from_source_with_offset(
+        source: &Source,
+        span: Span,
+        is_main: bool,
+        main_line_offset: usize,
+    ) -> Option<Self> {
+        let id = source.id();
         let range = source.range(span)?;
         let text = source.text();
 
@@ -249,7 +263,6 @@ impl SpanLocation {
         let end_col = text[end_line_start..range.end].chars().count();
 
         // Apply line offset for main file (when prelude is injected)
-        let is_main = id == world.main();
         let start_line = if is_main && main_line_offset > 0 {
             // If the diagnostic is from the prelude itself, skip it
             // (user doesn't need to see warnings from injected code)
@@ -525,6 +538,7 @@ pub fn format_diagnostics_with_options<W: World>(
 
 /// Disable colored output globally (for tests).
 #[cfg(all(test, feature = "colored-diagnostics"))]
+#[allow(dead_code)]
 pub fn disable_colors() {
     owo_colors::set_override(false);
 }
@@ -0,0 +1,115 @@
+//! Source locations for document elements.
+
+use std::fmt;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
+use typst::syntax::{FileId, Source, Span};
+#[cfg(feature = "scan")]
+use typst::World;
+
+use super::format::SpanLocation;
+
+/// A position in a source file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    /// File path (relative to project root, `@ns/name/path` for packages).
+    pub path: String,
+    /// Line number (1-indexed, excluding any injected prelude).
+    pub line: usize,
+    /// Column number (1-indexed).
+    pub column: usize,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.path, self.line, self.column)
+    }
+}
+
+/// Loads a source the first time one of its spans is resolved.
+type SourceLoader = Arc<dyn Fn(FileId) -> Option<Source> + Send + Sync>;
+
+/// Resolves element spans to [`SourceLocation`]s.
+///
+/// Keeps the world that evaluated the content (or a fixed set of sources),
+/// so spans can be resolved after the pipeline is done. Sources are loaded
+/// on first use and cached. Cheap to clone; clones share the cache.
+#[derive(Clone, Default)]
+pub struct SpanResolver {
+    sources: Arc<Mutex<FxHashMap<FileId, Option<Source>>>>,
+    load: Option<SourceLoader>,
+    main: Option<FileId>,
+    main_line_offset: usize,
+}
+
+impl fmt::Debug for SpanResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpanResolver")
+            .field("sources", &self.sources.lock().len())
+            .field("main", &self.main)
+            .field("main_line_offset", &self.main_line_offset)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SpanResolver {
+    /// Resolve spans with sources from `world`, loaded on demand.
+    ///
+    /// File access statistics and interrupts of `world` still apply to
+    /// these loads, so read the stats and disarm the interrupt first.
+    #[cfg(feature = "scan")]
+    pub(crate) fn from_world<W: World + 'static>(world: W, main_line_offset: usize) -> Self {
+        let main = world.main();
+        Self {
+            sources: Arc::default(),
+            load: Some(Arc::new(move |id| world.source(id).ok())),
+            main: Some(main),
+            main_line_offset,
+        }
+    }
+
+    /// Resolve spans in a single main source.
+    #[cfg(feature = "scan")]
+    pub(crate) fn from_source(source: Source, main_line_offset: usize) -> Self {
+        let id = source.id();
+        Self {
+            sources: Arc::new(Mutex::new(FxHashMap::from_iter([(id, Some(source))]))),
+            load: None,
+            main: Some(id),
+            main_line_offset,
+        }
+    }
+
+    /// Resolve a span to its source location.
+    ///
+    /// Returns `None` for detached spans, spans from unknown files and
+    /// spans inside an injected prelude.
+    pub fn resolve(&self, span: Span) -> Option<SourceLocation> {
+        let id = span.id()?;
+        let source = self.source(id)?;
+        let location = SpanLocation::from_source_with_offset(
+            &source,
+            span,
+            Some(id) == self.main,
+            self.main_line_offset,
+        )?;
+
+        Some(SourceLocation {
+            path: location.path,
+            line: location.start_line,
+            column: location.start_col + 1, // 1-indexed like `DiagnosticInfo`
+        })
+    }
+    /// Get a cached source, loading it on first use.
+    fn source(&self, id: FileId) -> Option<Source> {
+        if let Some(source) = self.sources.lock().get(&id) {
+            return source.clone();
+        }
+        // Load without holding the lock; a racing load gives the same source
+        let source = self.load.as_ref().and_then(|load| load(id));
+        self.sources.lock().insert(id, source.clone());
+        source
+    }
+}
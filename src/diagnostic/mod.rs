@@ -4,6 +4,7 @@ mod error;
 mod filter;
 mod format;
 mod info;
+mod location;
 
 // Re-export all public types
 pub use error::CompileError;
@@ -16,6 +17,7 @@ pub use info::{
     resolve_diagnostics, DiagnosticInfo, DiagnosticInfoDisplay, DiagnosticSummary, Diagnostics,
     DiagnosticsDisplay, SourceLine, TraceInfo,
 };
+pub use location::{SourceLocation, SpanResolver};
 
 // Re-export from typst for user convenience
 pub use typst::diag::{Severity as DiagnosticSeverity, SourceDiagnostic};
@@ -1,27 +1,48 @@
 //! HTML document wrapper.
 
-use typst::foundations::{Label, Selector};
+use rustc_hash::FxHashMap;
+use typst::foundations::{Content, Label, NativeElement, Selector};
 use typst::introspection::MetadataElem;
+use typst::model::HeadingElem;
 use typst::utils::PicoStr;
 
-use super::HtmlElement;
-#[cfg(feature = "svg")]
+use super::{is_heading, HeadingKeys, HtmlElement, Outline, OutlineBuilder};
+#[cfg(any(feature = "svg", feature = "png"))]
 use super::HtmlFrame;
+#[cfg(feature = "svg")]
+use super::FrameCache;
+#[cfg(feature = "svg")]
+use std::sync::Arc;
+use crate::codegen::{content_to_json, parse_selector, value_to_json};
+use crate::diagnostic::CompileError;
+use crate::world::LibraryStrategy;
 
-#[cfg(all(feature = "svg", feature = "batch"))]
+#[cfg(all(any(feature = "svg", feature = "png"), feature = "batch"))]
 use rayon::prelude::*;
 
 /// A compiled HTML document.
 ///
 /// This is a stable wrapper around the internal typst HTML document type.
 #[derive(Debug, Clone)]
-pub struct HtmlDocument(pub(crate) typst_html::HtmlDocument);
+pub struct HtmlDocument(
+    pub(crate) typst_html::HtmlDocument,
+    /// Library that selector queries are evaluated with.
+    LibraryStrategy,
+);
 
 impl HtmlDocument {
     /// Create a new HtmlDocument from a typst HtmlDocument.
+    ///
+    /// Queries use the global HTML library (without `sys.inputs`).
     #[inline]
     pub fn new(doc: typst_html::HtmlDocument) -> Self {
-        Self(doc)
+        Self(doc, LibraryStrategy::Global)
+    }
+
+    /// Evaluate queries with the library the document was compiled with.
+    pub(crate) fn with_library(mut self, library: LibraryStrategy) -> Self {
+        self.1 = library;
+        self
     }
 
     /// Get the root element of the document.
@@ -73,6 +94,92 @@ impl HtmlDocument {
             .collect()
     }
 
+    /// Query elements with a Typst selector expression, like `typst query`.
+    ///
+    /// The selector is evaluated with the document's standard library
+    /// (including its `sys.inputs`) and cannot read files, e.g.
+    /// `heading.where(level: 2)`, `figure`, `<label>` or
+    /// `selector(heading).or(figure)`. Results are in document order.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let headings = doc.query("heading.where(level: 2)")?;
+    /// let figures = doc.query("figure.where(kind: image)")?;
+    /// ```
+    pub fn query(&self, selector: &str) -> Result<Vec<serde_json::Value>, CompileError> {
+        Ok(self.query_content(selector)?.iter().map(content_to_json).collect())
+    }
+
+    /// Query elements and project a single field, like `typst query --field`.
+    ///
+    /// Elements without the field are skipped.
+    ///
+    /// ```ignore
+    /// let titles = doc.query_field("heading", "body")?;
+    /// let values = doc.query_field("<item>", "value")?;
+    /// ```
+    pub fn query_field(
+        &self,
+        selector: &str,
+        field: &str,
+    ) -> Result<Vec<serde_json::Value>, CompileError> {
+        Ok(self
+            .query_content(selector)?
+            .iter()
+            .filter_map(|elem| elem.get_by_name(field).ok())
+            .map(|value| value_to_json(&value))
+            .collect())
+    }
+
+    fn query_content(&self, selector: &str) -> Result<Vec<Content>, CompileError> {
+        let selector = parse_selector(selector, &self.1)?;
+        Ok(self.0.introspector.query(&selector).into_iter().collect())
+    }
+
+    /// Build the heading outline with anchor ids.
+    ///
+    /// Ids that Typst already assigned (e.g., to link targets) are kept, so
+    /// existing internal links stay valid.
+    ///
+    /// ```ignore
+    /// let outline = doc.outline();
+    /// let serializer = HtmlSerializer::new().with_hook(outline.anchors());
+    /// ```
+    pub fn outline(&self) -> Outline {
+        self.outline_with(OutlineBuilder::new())
+    }
+
+    /// Build the heading outline with a configured builder.
+    pub fn outline_with(&self, mut builder: OutlineBuilder) -> Outline {
+        // Ids on heading elements by key; other ids are left alone
+        let mut keys = HeadingKeys::default();
+        let mut existing = FxHashMap::default();
+        for elem in self.root().descendants().filter_map(|node| node.as_element()) {
+            if is_heading(elem) {
+                if let Some(key) = keys.next(elem.0.span)
+                    && let Some(id) = elem.id()
+                {
+                    existing.insert(key, id);
+                }
+            } else if let Some(id) = elem.id() {
+                builder.reserve(id);
+            }
+        }
+        for elem in self.0.introspector.all() {
+            if let Some(label) = elem.label()
+                && !elem.is::<HeadingElem>()
+            {
+                builder.reserve(label.resolve().to_string());
+            }
+        }
+
+        for elem in self.0.introspector.query(&HeadingElem::ELEM.select()) {
+            builder.push_elem(&elem, &existing);
+        }
+        builder.build()
+    }
+
     /// Render a frame to SVG.
     #[cfg(feature = "svg")]
     pub(crate) fn render_frame_svg(&self, frame: &HtmlFrame<'_>) -> String {
@@ -133,6 +240,85 @@ impl HtmlDocument {
         }
     }
 
+    /// Render multiple frames to SVG through a shared [`FrameCache`].
+    ///
+    /// Like [`render_frames`](Self::render_frames), but frames identical to
+    /// ones already rendered (from this or any other document) are reused.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let cache = FrameCache::new();
+    /// for doc in &docs {
+    ///     let svgs = doc.render_frames_cached(&collect_frames(doc), &cache);
+    /// }
+    /// ```
+    #[cfg(feature = "svg")]
+    pub fn render_frames_cached(&self, frames: &[HtmlFrame<'_>], cache: &FrameCache) -> Vec<Arc<str>> {
+        #[cfg(feature = "batch")]
+        {
+            frames.par_iter().map(|frame| cache.render(self, *frame)).collect()
+        }
+
+        #[cfg(not(feature = "batch"))]
+        {
+            frames.iter().map(|frame| cache.render(self, *frame)).collect()
+        }
+    }
+
+    /// Render a frame to PNG.
+    #[cfg(feature = "png")]
+    pub(crate) fn render_frame_png(
+        &self,
+        frame: &HtmlFrame<'_>,
+        pixel_per_pt: f32,
+    ) -> Result<Vec<u8>, CompileError> {
+        render_raw_frame_png(frame.0, pixel_per_pt)
+    }
+
+    /// Render multiple frames to PNG.
+    ///
+    /// Raster counterpart of [`render_frames`](Self::render_frames): frames are
+    /// rendered in parallel when the `batch` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `frames` - Slice of frames to render
+    /// * `pixel_per_pt` - Resolution (e.g., `2.0` for 144 DPI)
+    ///
+    /// # Returns
+    ///
+    /// PNG bytes in the same order as input frames, or the first encoding error.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let frames: Vec<_> = collect_frames(&doc);
+    /// let pngs = doc.render_frames_png(&frames, 2.0)?;
+    /// ```
+    #[cfg(feature = "png")]
+    pub fn render_frames_png(
+        &self,
+        frames: &[HtmlFrame<'_>],
+        pixel_per_pt: f32,
+    ) -> Result<Vec<Vec<u8>>, CompileError> {
+        #[cfg(feature = "batch")]
+        {
+            frames
+                .par_iter()
+                .map(|frame| render_raw_frame_png(frame.0, pixel_per_pt))
+                .collect()
+        }
+
+        #[cfg(not(feature = "batch"))]
+        {
+            frames
+                .iter()
+                .map(|frame| render_raw_frame_png(frame.0, pixel_per_pt))
+                .collect()
+        }
+    }
+
     /// Get the inner typst document.
     #[inline]
     pub fn into_inner(self) -> typst_html::HtmlDocument {
@@ -152,8 +338,181 @@ fn metadata_to_json(elem: &typst::foundations::Content) -> Option<serde_json::Va
         .and_then(|meta| serde_json::to_value(&meta.value).ok())
 }
 
+/// Rasterize a frame on a transparent page and encode it as PNG.
+#[cfg(feature = "png")]
+fn render_raw_frame_png(
+    frame: &typst_html::HtmlFrame,
+    pixel_per_pt: f32,
+) -> Result<Vec<u8>, CompileError> {
+    use typst::foundations::{Content, Smart};
+    use typst::layout::Page;
+
+    let page = Page {
+        frame: frame.inner.clone(),
+        fill: Smart::Custom(None),
+        numbering: None,
+        supplement: Content::empty(),
+        number: 1,
+    };
+
+    typst_render::render(&page, pixel_per_pt)
+        .encode_png()
+        .map_err(|e| CompileError::image_export(e.to_string()))
+}
+
 impl From<typst_html::HtmlDocument> for HtmlDocument {
     fn from(doc: typst_html::HtmlDocument) -> Self {
         Self::new(doc)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::compile_source;
+
+    const SOURCE: &str = r#"= Intro
+== First <first>
+== Second
+#metadata((id: 1)) <item>
+#metadata((id: 2)) <item>"#;
+
+    #[test]
+    fn test_query_selectors() {
+        let doc = compile_source(SOURCE).into_document();
+
+        let h2 = doc.query("heading.where(level: 2)").unwrap();
+        assert_eq!(h2.len(), 2);
+        assert_eq!(h2[0]["func"], "heading");
+        assert_eq!(h2[0]["level"], 2);
+
+        assert_eq!(doc.query("heading").unwrap().len(), 3);
+        assert_eq!(doc.query("<first>").unwrap().len(), 1);
+        assert_eq!(doc.query("selector(heading).or(<item>)").unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_query_field() {
+        let doc = compile_source(SOURCE).into_document();
+
+        let ids = doc.query_field("<item>", "value").unwrap();
+        assert_eq!(ids, [serde_json::json!({"id": 1}), serde_json::json!({"id": 2})]);
+
+        let levels = doc.query_field("heading", "level").unwrap();
+        assert_eq!(levels, [1, 2, 2]);
+    }
+
+    #[test]
+    fn test_query_invalid_selector() {
+        let doc = compile_source(SOURCE).into_document();
+        assert!(matches!(doc.query("heading.where("), Err(CompileError::Query { .. })));
+        assert!(matches!(doc.query("42"), Err(CompileError::Query { .. })));
+        assert!(matches!(doc.query("undefined_elem"), Err(CompileError::Query { .. })));
+    }
+
+    #[test]
+    fn test_query_uses_document_inputs() {
+        use crate::process::compile::Compiler;
+        use crate::process::WithInputs;
+
+        let doc = Compiler::new(std::path::Path::new("."))
+            .with_inputs([("level", "2")])
+            .with_source("query.typ", SOURCE)
+            .compile()
+            .unwrap()
+            .into_document();
+        let headings = doc.query("heading.where(level: int(sys.inputs.level))").unwrap();
+        assert_eq!(headings.len(), 2);
+        assert!(doc.query(r#"heading.where(body: read("query.typ"))"#).is_err());
+    }
+
+    #[test]
+    fn test_outline_and_anchors() {
+        let doc = compile_source(
+            r#"#outline()
+= Getting Started
+== Install <install>
+== Install
+= Usage
+See #link(<install>)[install] and
+#context link(query(heading.where(body: [Usage])).first().location())[usage].
+=== Deep Dive
+#heading(outlined: false)[Hidden]"#,
+        ).into_document();
+
+        let outline = doc.outline();
+        let ids: Vec<_> = outline.iter().map(|e| e.id.as_str()).collect();
+        // The outline title and `outlined: false` headings are excluded
+        assert_eq!(ids, ["getting-started", "install", "install-1", "usage", "deep-dive"]);
+        assert_eq!(outline.entries()[0].children.len(), 2);
+        assert_eq!(outline.entries()[1].children[0].level, 3);
+
+        let html = crate::html::HtmlSerializer::new()
+            .compact()
+            .with_hook(outline.anchors())
+            .serialize(&doc)
+            .unwrap();
+        assert!(html.contains(r#"<h2 id="getting-started">"#));
+        assert!(html.contains(r#"<h3 id="install">"#));
+        assert!(html.contains(r#"<h3 id="install-1">"#));
+        assert!(html.contains(r#"<h4 id="deep-dive">"#));
+        // Generated `loc-*` ids (outline entries, location links) are replaced
+        assert!(html.contains(r##"href="#install""##));
+        assert!(html.contains(r##"<a href="#usage">usage</a>"##));
+        assert!(!html.contains("loc-"));
+    }
+
+    #[test]
+    fn test_outline_loop_headings() {
+        let doc = compile_source(
+            r#"#for name in ("Alpha", "Beta", "Alpha") [= #name]
+#figure(caption: [Gamma])[x] <gamma>
+#html.elem("div", attrs: (id: "delta"))[]
+= Gamma
+= Delta"#,
+        ).into_document();
+
+        let outline = doc.outline();
+        let ids: Vec<_> = outline.iter().map(|e| e.id.as_str()).collect();
+        // Loop headings share a span but get their own ids; slugs avoid
+        // the label and id already on the page
+        assert_eq!(ids, ["alpha", "beta", "alpha-1", "gamma-1", "delta-1"]);
+
+        let html = crate::html::HtmlSerializer::new()
+            .compact()
+            .with_hook(outline.anchors())
+            .serialize(&doc)
+            .unwrap();
+        assert!(html.contains(r#"<h2 id="alpha">Alpha</h2>"#));
+        assert!(html.contains(r#"<h2 id="beta">Beta</h2>"#));
+        assert!(html.contains(r#"<h2 id="alpha-1">Alpha</h2>"#));
+        assert!(html.contains(r#"<h2 id="gamma-1">Gamma</h2>"#));
+        assert!(html.contains(r#"<div id="delta"></div>"#));
+    }
+
+    #[test]
+    fn test_outline_hidden_and_duplicate_labels() {
+        let doc = compile_source(
+            r#"#heading(outlined: false)[Notes] <notes>
+= Notes
+= First <part>
+= Second <part>
+See #link(<notes>)[notes]."#,
+        ).into_document();
+
+        let outline = doc.outline();
+        let ids: Vec<_> = outline.iter().map(|e| e.id.as_str()).collect();
+        // The hidden heading keeps its label; the repeated label gets a suffix
+        assert_eq!(ids, ["notes-1", "part", "part-1"]);
+
+        let html = crate::html::HtmlSerializer::new()
+            .compact()
+            .with_hook(outline.anchors())
+            .serialize(&doc)
+            .unwrap();
+        assert_eq!(html.matches(r#"id="notes""#).count(), 1);
+        assert!(html.contains(r#"<h2 id="notes-1">Notes</h2>"#));
+        assert!(html.contains(r#"<h2 id="part">First</h2>"#));
+        assert!(html.contains(r#"<h2 id="part-1">Second</h2>"#));
+    }
+}
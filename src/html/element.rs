@@ -1,6 +1,9 @@
 //! HTML element wrapper.
 
+use std::ops::ControlFlow;
+
 use super::node::HtmlNode;
+use super::visit::{walk_breadth_first, walk_depth_first, BreadthFirst, Descendants, Visitor};
 
 /// An HTML element.
 ///
@@ -119,4 +122,76 @@ impl<'a> HtmlElement<'a> {
     pub fn is_empty(&self) -> bool {
         self.0.children.is_empty()
     }
+
+    // =========================================================================
+    // Traversal
+    // =========================================================================
+
+    /// Iterate over all descendant nodes depth-first (document order).
+    ///
+    /// The element itself is not included.
+    #[inline]
+    pub fn descendants(&self) -> Descendants<'a> {
+        Descendants::new(*self)
+    }
+
+    /// Iterate over all descendant nodes breadth-first (level order).
+    ///
+    /// The element itself is not included.
+    #[inline]
+    pub fn descendants_breadth_first(&self) -> BreadthFirst<'a> {
+        BreadthFirst::new(*self)
+    }
+
+    /// Walk descendants depth-first with a [`Visitor`].
+    ///
+    /// `enter` is called before a node's children, `leave` after an element's
+    /// children. Returns `ControlFlow::Break(())` if the visitor stopped early.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut links = Vec::new();
+    /// root.walk(&mut |node: HtmlNode<'_>| {
+    ///     if let Some(href) = node.as_element().and_then(|e| e.get_attr("href")) {
+    ///         links.push(href);
+    ///     }
+    ///     ControlFlow::Continue(())
+    /// });
+    /// ```
+    pub fn walk<V: Visitor<'a> + ?Sized>(&self, visitor: &mut V) -> ControlFlow<()> {
+        walk_depth_first(*self, visitor)
+    }
+
+    /// Walk descendants breadth-first with a [`Visitor`].
+    ///
+    /// Only `enter` is called; `leave` has no meaning in level order.
+    pub fn walk_breadth_first<V: Visitor<'a> + ?Sized>(&self, visitor: &mut V) -> ControlFlow<()> {
+        walk_breadth_first(*self, visitor)
+    }
+
+    /// Find all descendant elements with the given tag, in document order.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// for img in doc.root().find_by_tag("img") {
+    ///     println!("{:?}", img.get_attr("src"));
+    /// }
+    /// ```
+    pub fn find_by_tag<'t>(&self, tag: &'t str) -> impl Iterator<Item = HtmlElement<'a>> + 't
+    where
+        'a: 't,
+    {
+        self.descendants()
+            .filter_map(|node| node.as_element())
+            .filter(move |elem| elem.0.tag.resolve().as_str() == tag)
+    }
+
+    /// Find the first descendant element with the given `id` attribute.
+    pub fn find_by_id(&self, id: &str) -> Option<HtmlElement<'a>> {
+        self.descendants()
+            .filter_map(|node| node.as_element())
+            .find(|elem| elem.get_attr("id").as_deref() == Some(id))
+    }
 }
@@ -0,0 +1,244 @@
+//! Automatic frame embedding for finished HTML pages.
+//!
+//! [`FrameMode`] selects how [`HtmlFrame`]s are written when producing HTML:
+//! - [`FrameMode::InlineSvg`] - `<svg>` inline, wrapped for baseline alignment
+//! - [`FrameMode::ExternalSvg`] - `<img>` referencing content-hashed `.svg` files
+//! - [`FrameMode::DataUri`] - `<img>` with a base64 `data:` URI
+//!
+//! # Example
+//!
+//! ```ignore
+//! let output = result.html_with(FrameMode::external("/assets/frames/"))?;
+//! output.write_assets("public/assets/frames")?;
+//! std::fs::write("public/index.html", &output.html)?;
+//! ```
+
+use std::io;
+use std::path::Path;
+
+use super::frame::inline_svg;
+use super::serialize::{HtmlSerializer, NodeAction, SerializeHook};
+use super::{FrameCache, HtmlDocument, HtmlFrame, HtmlNode};
+use crate::diagnostic::CompileError;
+
+/// How frames are written when producing HTML.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FrameMode {
+    /// Inline `<svg>` wrapped in a `<span>` with `vertical-align`
+    /// (see [`HtmlFrame::to_inline_svg`]).
+    #[default]
+    InlineSvg,
+    /// `<img>` referencing an external SVG file named by content hash.
+    ///
+    /// The files are returned in [`HtmlOutput::assets`]; identical frames
+    /// share one file.
+    ExternalSvg {
+        /// Prefix for the `src` attribute, e.g. `/assets/frames/`.
+        url_prefix: String,
+    },
+    /// `<img>` with the SVG embedded as a base64 `data:` URI.
+    DataUri,
+}
+
+impl FrameMode {
+    /// External SVG files referenced with the given URL prefix.
+    pub fn external(url_prefix: impl Into<String>) -> Self {
+        Self::ExternalSvg {
+            url_prefix: url_prefix.into(),
+        }
+    }
+}
+
+/// An external file produced while writing HTML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameAsset {
+    /// File name (content hash plus extension), e.g. `3f2a…c1.svg`.
+    pub name: String,
+    /// File content.
+    pub data: Vec<u8>,
+}
+
+/// HTML with any external frame files it references.
+#[derive(Debug, Clone, Default)]
+pub struct HtmlOutput {
+    /// The serialized HTML.
+    pub html: Vec<u8>,
+    /// Files referenced by the HTML (only for [`FrameMode::ExternalSvg`]).
+    pub assets: Vec<FrameAsset>,
+}
+
+impl HtmlOutput {
+    /// Write all assets into `dir`, skipping files that already exist.
+    ///
+    /// Names are content hashes, so an existing file has the same content.
+    pub fn write_assets(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        if self.assets.is_empty() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(dir)?;
+        for asset in &self.assets {
+            let path = dir.join(&asset.name);
+            if !path.exists() {
+                std::fs::write(path, &asset.data)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Serialize a document, writing frames according to `mode`.
+///
+/// Frames are rendered through `cache` when given.
+pub(crate) fn html_with_frames(
+    doc: &HtmlDocument,
+    mode: &FrameMode,
+    cache: Option<&FrameCache>,
+) -> Result<HtmlOutput, CompileError> {
+    let mut assets = Vec::new();
+    let hook = FrameHook {
+        doc,
+        mode,
+        cache,
+        assets: &mut assets,
+    };
+    let html = HtmlSerializer::new().with_hook(hook).serialize(doc)?;
+    Ok(HtmlOutput {
+        html: html.into_bytes(),
+        assets,
+    })
+}
+
+/// Serializer hook that replaces frames.
+struct FrameHook<'a> {
+    doc: &'a HtmlDocument,
+    mode: &'a FrameMode,
+    cache: Option<&'a FrameCache>,
+    assets: &'a mut Vec<FrameAsset>,
+}
+
+impl FrameHook<'_> {
+    fn svg(&self, frame: HtmlFrame<'_>) -> String {
+        match self.cache {
+            Some(cache) => cache.render(self.doc, frame).to_string(),
+            None => frame.to_svg(self.doc),
+        }
+    }
+}
+
+impl SerializeHook for FrameHook<'_> {
+    fn node(&mut self, node: HtmlNode<'_>) -> NodeAction {
+        let Some(frame) = node.as_frame() else {
+            return NodeAction::Keep;
+        };
+
+        let html = match self.mode {
+            FrameMode::InlineSvg => inline_svg(&frame, &self.svg(frame)),
+            FrameMode::ExternalSvg { url_prefix } => {
+                let svg = self.svg(frame);
+                let name = format!("{:032x}.svg", typst::utils::hash128(&svg));
+                let src = format!("{url_prefix}{name}");
+                if !self.assets.iter().any(|a| a.name == name) {
+                    self.assets.push(FrameAsset {
+                        name,
+                        data: svg.into_bytes(),
+                    });
+                }
+                img_tag(&frame, &src)
+            }
+            FrameMode::DataUri => {
+                let svg = self.svg(frame);
+                let src = format!("data:image/svg+xml;base64,{}", base64(svg.as_bytes()));
+                img_tag(&frame, &src)
+            }
+        };
+        NodeAction::Replace(html)
+    }
+}
+
+/// `<img>` sized in em like the inline SVG, with the same baseline alignment.
+fn img_tag(frame: &HtmlFrame<'_>, src: &str) -> String {
+    let size = frame.text_size();
+    format!(
+        r#"<img class="typst-frame" src="{}" alt="" style="width: {:.4}em; height: {:.4}em; vertical-align: {:.4}em">"#,
+        src.replace('&', "&amp;").replace('"', "&quot;"),
+        frame.width() / size,
+        frame.height() / size,
+        frame.vertical_align_em(),
+    )
+}
+
+/// Standard base64 with padding.
+fn base64(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(TABLE[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::compile_source;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_inline_svg() {
+        let result = compile_source("#html.frame(rect(width: 4pt))");
+        let output = result.html_with(FrameMode::InlineSvg).unwrap();
+        let html = String::from_utf8(output.html).unwrap();
+
+        assert!(html.contains(r#"<span style="vertical-align: "#));
+        assert!(html.contains("<svg"));
+        assert!(output.assets.is_empty());
+    }
+
+    #[test]
+    fn test_external_svg_dedup() {
+        let result = compile_source(
+            "#html.frame(rect(width: 4pt))\n#html.frame(rect(width: 4pt))\n#html.frame(circle())",
+        );
+        let output = result.html_with(FrameMode::external("/frames/")).unwrap();
+        let html = String::from_utf8(output.html.clone()).unwrap();
+
+        // Two identical rects share one file
+        assert_eq!(html.matches("<img").count(), 3);
+        assert_eq!(output.assets.len(), 2);
+        assert!(html.contains(&format!(r#"src="/frames/{}""#, output.assets[0].name)));
+        assert!(!html.contains("<svg"));
+
+        let dir = TempDir::new().unwrap();
+        output.write_assets(dir.path()).unwrap();
+        assert!(dir.path().join(&output.assets[0].name).exists());
+    }
+
+    #[test]
+    fn test_data_uri() {
+        let result = compile_source("#html.frame(rect(width: 4pt))");
+        let output = result.html_with(FrameMode::DataUri).unwrap();
+        let html = String::from_utf8(output.html).unwrap();
+
+        assert!(html.contains(r#"src="data:image/svg+xml;base64,"#));
+        assert!(output.assets.is_empty());
+    }
+}
@@ -1,12 +1,24 @@
 //! HTML frame wrapper.
 
-#[cfg(feature = "svg")]
+#[cfg(any(feature = "svg", feature = "png"))]
 use super::HtmlDocument;
+#[cfg(feature = "png")]
+use crate::diagnostic::CompileError;
+
+/// Wrap a rendered frame SVG in a `<span>` with its `vertical-align` offset.
+#[cfg(feature = "svg")]
+pub(crate) fn inline_svg(frame: &HtmlFrame<'_>, svg: &str) -> String {
+    format!(
+        r#"<span style="vertical-align: {:.4}em">{}</span>"#,
+        frame.vertical_align_em(),
+        svg
+    )
+}
 
-/// A frame that should be rendered as SVG.
+/// A frame that should be rendered as SVG (or PNG).
 ///
 /// Frames contain typst-rendered content (math, images, plots, etc.)
-/// that needs to be embedded as inline SVG in HTML output.
+/// that needs to be embedded as inline SVG or raster images in HTML output.
 #[derive(Debug, Clone, Copy)]
 pub struct HtmlFrame<'a>(pub(crate) &'a typst_html::HtmlFrame);
 
@@ -111,12 +123,54 @@ impl<'a> HtmlFrame<'a> {
     /// ```
     #[cfg(feature = "svg")]
     pub fn to_inline_svg(&self, doc: &HtmlDocument) -> String {
-        let svg = self.to_svg(doc);
-        let align = self.vertical_align_em();
-        format!(
-            r#"<span style="vertical-align: {:.4}em">{}</span>"#,
-            align, svg
-        )
+        inline_svg(self, &self.to_svg(doc))
+    }
+
+    /// Render this frame to PNG bytes.
+    ///
+    /// `pixel_per_pt` controls the resolution: `1.0` is 72 DPI, `2.0` is
+    /// 144 DPI (recommended for high-density displays). The background is
+    /// transparent.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let png = frame.to_png(&doc, 2.0)?;
+    /// std::fs::write("formula.png", png)?;
+    /// ```
+    ///
+    /// # Note
+    ///
+    /// This method requires the `png` feature to be enabled.
+    #[cfg(feature = "png")]
+    pub fn to_png(&self, doc: &HtmlDocument, pixel_per_pt: f32) -> Result<Vec<u8>, CompileError> {
+        doc.render_frame_png(self, pixel_per_pt)
     }
 }
 
+#[cfg(all(test, feature = "png"))]
+mod tests {
+    use std::fs;
+    use tempfile::TempDir;
+
+    use crate::html::collect_frames;
+    use crate::process::compile::Compiler;
+
+    #[test]
+    fn test_frame_to_png() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("test.typ");
+        fs::write(&file, "#html.frame(rect(width: 10pt, height: 5pt, fill: red))").unwrap();
+
+        let result = Compiler::new(dir.path()).with_path(&file).compile().unwrap();
+        let doc = result.document();
+        let frame = collect_frames(doc)[0];
+
+        let png = frame.to_png(doc, 2.0).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        let pngs = doc.render_frames_png(&[frame, frame], 1.0).unwrap();
+        assert_eq!(pngs.len(), 2);
+        assert!(pngs[0].len() < png.len());
+    }
+}
@@ -0,0 +1,238 @@
+//! Cross-document SVG frame cache.
+//!
+//! Identical frames (same formula at the same text size) render to identical
+//! SVG, even when they come from different documents. [`FrameCache`] keys
+//! rendered SVG by a hash of the frame contents, so repeated frames in a
+//! batch are rendered once.
+//!
+//! Source spans are excluded from the key (the same formula in two files
+//! has different spans but renders identically). Links to locations are
+//! keyed by their resolved HTML id, since that is what ends up in the SVG.
+
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use rustc_hash::FxHashMap;
+use typst::introspection::Introspector;
+use typst::layout::{Frame, FrameItem};
+use typst::model::Destination;
+
+use super::{HtmlDocument, HtmlFrame};
+
+/// Thread-safe SVG cache shared across documents.
+///
+/// # Example
+///
+/// ```ignore
+/// let cache = Arc::new(FrameCache::new());
+/// let batcher = Batcher::new(root).with_frame_cache(cache.clone());
+///
+/// for result in batcher.batch_compile(&files)?.into_iter().flatten() {
+///     let output = result.html_with(FrameMode::InlineSvg)?;
+/// }
+/// println!("{:?}", cache.stats());
+/// ```
+#[derive(Debug, Default)]
+pub struct FrameCache {
+    entries: RwLock<FxHashMap<u128, Arc<str>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// Hit/miss counters of a [`FrameCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameCacheStats {
+    /// Frames served from the cache.
+    pub hits: u64,
+    /// Frames rendered and inserted.
+    pub misses: u64,
+    /// Distinct frames currently cached.
+    pub entries: usize,
+}
+
+impl FrameCacheStats {
+    /// Fraction of lookups served from the cache (`0.0` when unused).
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+impl FrameCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render a frame to SVG, reusing a previous rendering of an identical frame.
+    pub fn render(&self, doc: &HtmlDocument, frame: HtmlFrame<'_>) -> Arc<str> {
+        let key = frame_key(frame.0, &doc.0.introspector);
+
+        if let Some(svg) = self.entries.read().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return svg.clone();
+        }
+
+        // Render outside the lock; concurrent misses on one key render twice
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let svg: Arc<str> = doc.render_frame_svg(&frame).into();
+        self.entries
+            .write()
+            .unwrap()
+            .entry(key)
+            .or_insert(svg)
+            .clone()
+    }
+
+    /// Get the hit/miss counters.
+    pub fn stats(&self) -> FrameCacheStats {
+        FrameCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.len(),
+        }
+    }
+
+    /// Number of distinct cached frames.
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// Check if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all entries and reset the counters.
+    pub fn clear(&self) {
+        self.entries.write().unwrap().clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Hash everything that affects the rendered SVG.
+fn frame_key(frame: &typst_html::HtmlFrame, introspector: &Introspector) -> u128 {
+    typst::utils::hash128(&FrameKey {
+        frame,
+        introspector,
+    })
+}
+
+struct FrameKey<'a> {
+    frame: &'a typst_html::HtmlFrame,
+    introspector: &'a Introspector,
+}
+
+impl Hash for FrameKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.frame.text_size.hash(state);
+        self.frame.id.hash(state);
+        self.frame.link_points.hash(state);
+        hash_frame(&self.frame.inner, self.introspector, state);
+    }
+}
+
+/// Hash a frame like `Frame::hash`, but without spans and tags.
+fn hash_frame<H: Hasher>(frame: &Frame, introspector: &Introspector, state: &mut H) {
+    frame.size().hash(state);
+    frame.baseline().hash(state);
+    for (pos, item) in frame.items() {
+        std::mem::discriminant(item).hash(state);
+        match item {
+            FrameItem::Group(group) => {
+                pos.hash(state);
+                group.transform.hash(state);
+                group.clip.hash(state);
+                hash_frame(&group.frame, introspector, state);
+            }
+            FrameItem::Text(text) => {
+                pos.hash(state);
+                text.font.hash(state);
+                text.size.hash(state);
+                text.fill.hash(state);
+                text.stroke.hash(state);
+                text.lang.hash(state);
+                text.region.hash(state);
+                text.text.hash(state);
+                for glyph in &text.glyphs {
+                    glyph.id.hash(state);
+                    glyph.x_advance.hash(state);
+                    glyph.x_offset.hash(state);
+                    glyph.y_advance.hash(state);
+                    glyph.y_offset.hash(state);
+                    glyph.range.hash(state);
+                }
+            }
+            FrameItem::Shape(shape, _) => {
+                pos.hash(state);
+                shape.hash(state);
+            }
+            FrameItem::Image(image, size, _) => {
+                pos.hash(state);
+                image.hash(state);
+                size.hash(state);
+            }
+            FrameItem::Link(dest, size) => {
+                pos.hash(state);
+                size.hash(state);
+                match dest {
+                    Destination::Location(loc) => introspector.html_id(*loc).hash(state),
+                    Destination::Position(_) => {}
+                    Destination::Url(url) => url.hash(state),
+                }
+            }
+            // Tags carry locations but are not rendered
+            FrameItem::Tag(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::collect_frames;
+    use crate::process::compile::Compiler;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn compile(dir: &TempDir, name: &str, source: &str) -> HtmlDocument {
+        let file = dir.path().join(name);
+        fs::write(&file, source).unwrap();
+        Compiler::new(dir.path())
+            .with_path(&file)
+            .compile()
+            .unwrap()
+            .into_document()
+    }
+
+    #[test]
+    fn test_frame_cache_across_documents() {
+        let dir = TempDir::new().unwrap();
+        let frame = "#html.frame(rect(width: 4pt, fill: red))";
+        // Same frame at different source positions, plus a different size
+        let a = compile(&dir, "a.typ", &format!("{frame}\n{frame}"));
+        let b = compile(&dir, "b.typ", &format!("= Heading\n\n{frame}\n#text(20pt)[{frame}]"));
+
+        let cache = FrameCache::new();
+        let svgs_a: Vec<_> = collect_frames(&a).into_iter().map(|f| cache.render(&a, f)).collect();
+        let svgs_b: Vec<_> = collect_frames(&b).into_iter().map(|f| cache.render(&b, f)).collect();
+
+        assert_eq!(svgs_a[0], svgs_b[0]);
+        assert_ne!(svgs_b[0], svgs_b[1]);
+        assert_eq!(&*svgs_a[0], a.render_frame_svg(&collect_frames(&a)[0]));
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 2, 2));
+        assert_eq!(stats.hit_rate(), 0.5);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.stats(), FrameCacheStats::default());
+    }
+}
@@ -2,10 +2,39 @@
 
 mod document;
 mod element;
+#[cfg(feature = "svg")]
+mod embed;
 mod frame;
+#[cfg(feature = "svg")]
+mod frame_cache;
 mod node;
+mod outline;
+mod serialize;
+mod visit;
 
 pub use document::HtmlDocument;
 pub use element::HtmlElement;
+#[cfg(feature = "svg")]
+pub use embed::{FrameAsset, FrameMode, HtmlOutput};
+#[cfg(feature = "svg")]
+pub(crate) use embed::html_with_frames;
 pub use frame::HtmlFrame;
+#[cfg(feature = "svg")]
+pub use frame_cache::{FrameCache, FrameCacheStats};
 pub use node::{HtmlNode, NodeKind};
+pub use outline::{slugify, HeadingAnchors, Outline, OutlineBuilder, OutlineEntry};
+pub(crate) use outline::{is_heading, HeadingKeys};
+pub use serialize::{AttrOrder, HtmlFormat, HtmlSerializer, NodeAction, SerializeHook};
+pub use visit::{collect_frames, BreadthFirst, Descendants, Visitor};
+
+/// Compile `source` as the main file of a temporary project.
+#[cfg(test)]
+pub(crate) fn compile_source(source: &str) -> crate::process::compile::CompileResult {
+    let dir = tempfile::TempDir::new().unwrap();
+    let file = dir.path().join("test.typ");
+    std::fs::write(&file, source).unwrap();
+    crate::process::compile::Compiler::new(dir.path())
+        .with_path(&file)
+        .compile()
+        .unwrap()
+}
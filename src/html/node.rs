@@ -15,7 +15,7 @@ pub enum NodeKind<'a> {
     Element(HtmlElement<'a>),
     /// Plain text content.
     Text(&'a str),
-    /// A frame that should be rendered as SVG.
+    /// A frame that should be rendered as SVG (or PNG).
     Frame(HtmlFrame<'a>),
     /// An introspection tag (usually ignored during conversion).
     Tag,
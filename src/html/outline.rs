@@ -0,0 +1,406 @@
+//! Hierarchical heading outline with stable anchor ids.
+//!
+//! [`Outline`] nests headings by level and assigns each one an anchor id:
+//! - Headings with a label use the label (`= Intro <intro>` → `intro`);
+//!   a label that an earlier heading already uses gets a numeric suffix
+//! - Other headings use a slug of their text, de-duplicated with a
+//!   numeric suffix (`setup`, `setup-1`, …) against other headings and
+//!   the labels and ids of other elements
+//!
+//! [`Outline::anchors`] returns a [`SerializeHook`] that writes the same ids
+//! onto the heading elements, so a table of contents and the page agree.
+//! Ids that Typst generated for link targets (`loc-1`, …) are replaced, and
+//! `href`s pointing at them are rewritten.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let outline = result.document().outline();
+//! for entry in outline.iter() {
+//!     println!("{}<a href=\"#{}\">{}</a>", "  ".repeat(entry.level as usize), entry.id, entry.text);
+//! }
+//!
+//! let html = result.html_with_serializer(&mut HtmlSerializer::new().with_hook(outline.anchors()))?;
+//! ```
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use typst::foundations::{Content, StyleChain};
+use typst::model::HeadingElem;
+use typst::syntax::Span;
+
+use super::serialize::SerializeHook;
+use super::HtmlElement;
+
+/// A heading in an [`Outline`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineEntry {
+    /// Heading level (1-based).
+    pub level: u8,
+    /// Heading text (plain text from body).
+    pub text: String,
+    /// Anchor id, unique within the outline.
+    pub id: String,
+    /// Headings nested below this one.
+    pub children: Vec<OutlineEntry>,
+}
+
+/// Nested tree of headings with anchor ids.
+///
+/// Build one with [`OutlineBuilder`], [`HtmlDocument::outline`](super::HtmlDocument::outline)
+/// or `ScanResult::outline`.
+#[derive(Debug, Clone, Default)]
+pub struct Outline {
+    entries: Vec<OutlineEntry>,
+    /// Anchor ids by heading key, for [`Outline::anchors`].
+    anchors: FxHashMap<HeadingKey, String>,
+    /// Generated ids replaced by anchor ids.
+    renames: FxHashMap<String, String>,
+}
+
+impl Outline {
+    /// Get the top-level entries.
+    pub fn entries(&self) -> &[OutlineEntry] {
+        &self.entries
+    }
+
+    /// Iterate over all entries depth-first (document order).
+    pub fn iter(&self) -> impl Iterator<Item = &OutlineEntry> {
+        let mut stack: Vec<&OutlineEntry> = self.entries.iter().rev().collect();
+        std::iter::from_fn(move || {
+            let entry = stack.pop()?;
+            stack.extend(entry.children.iter().rev());
+            Some(entry)
+        })
+    }
+
+    /// Check if the outline has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the anchor id of the heading with the given key.
+    pub(crate) fn anchor(&self, key: HeadingKey) -> Option<&str> {
+        self.anchors.get(&key).map(String::as_str)
+    }
+
+    /// Serializer hook that writes the anchor ids onto heading elements.
+    ///
+    /// Headings are matched by source span and their position among the
+    /// headings with that span, so this only works for outlines built from
+    /// a document or scan result, and only for output generated from the
+    /// same document.
+    pub fn anchors(&self) -> HeadingAnchors<'_> {
+        HeadingAnchors {
+            anchors: &self.anchors,
+            renames: &self.renames,
+            keys: HeadingKeys::default(),
+        }
+    }
+}
+
+/// Builder for an [`Outline`].
+///
+/// Ids are assigned in [`build`](Self::build), after all labels are known,
+/// so a slug never takes an id that a later label claims.
+#[derive(Debug, Clone, Default)]
+pub struct OutlineBuilder {
+    max_level: Option<u8>,
+    headings: Vec<PendingHeading>,
+    keys: HeadingKeys,
+    /// Ids used by other elements on the page.
+    reserved: FxHashSet<String>,
+}
+
+#[derive(Debug, Clone)]
+struct PendingHeading {
+    level: u8,
+    text: String,
+    label: Option<String>,
+    key: Option<HeadingKey>,
+    /// Id Typst generated for the heading, if any.
+    generated_id: Option<String>,
+}
+
+impl OutlineBuilder {
+    /// Create an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leave headings deeper than `level` out of the tree.
+    ///
+    /// They still receive ids, so anchors are the same at every depth.
+    pub fn with_max_level(mut self, level: u8) -> Self {
+        self.max_level = Some(level);
+        self
+    }
+
+    /// Add a heading in document order.
+    pub fn push(&mut self, level: u8, text: impl Into<String>, label: Option<&str>) {
+        self.headings.push(PendingHeading {
+            level,
+            text: text.into(),
+            label: label.map(Into::into),
+            key: None,
+            generated_id: None,
+        });
+    }
+
+    /// Keep slugs from taking `id`, e.g. because another element on the
+    /// page already uses it.
+    pub fn reserve(&mut self, id: impl Into<String>) {
+        self.reserved.insert(id.into());
+    }
+
+    /// Add a heading element (label and key are taken from the element).
+    ///
+    /// Every heading element must be pushed in document order, including
+    /// ones that are not outlined, so that keys line up with the page.
+    /// `existing` holds the ids Typst wrote onto heading elements.
+    pub(crate) fn push_elem(&mut self, elem: &Content, existing: &FxHashMap<HeadingKey, String>) {
+        let Some(heading) = elem.to_packed::<HeadingElem>() else {
+            return;
+        };
+        let key = self.keys.next(elem.span());
+        let label = elem.label().map(|label| label.resolve().to_string());
+        let id = key.and_then(|key| existing.get(&key)).cloned();
+        if !heading.outlined.get(StyleChain::default()) {
+            // Not in the outline, but its ids stay on the page
+            self.reserved.extend(label.into_iter().chain(id));
+            return;
+        }
+        self.headings.push(PendingHeading {
+            level: heading.resolve_level(StyleChain::default()).get() as u8,
+            text: heading.body.plain_text().to_string(),
+            generated_id: id.filter(|id| Some(id) != label.as_ref()),
+            label,
+            key,
+        });
+    }
+
+    /// Assign ids and nest the headings.
+    pub fn build(self) -> Outline {
+        let mut taken = self.reserved;
+        taken.extend(self.headings.iter().filter_map(|h| h.label.clone()));
+
+        let mut labels = FxHashSet::default();
+        let mut anchors = FxHashMap::default();
+        let mut renames = FxHashMap::default();
+        let mut flat = Vec::new();
+        for heading in self.headings {
+            let id = match heading.label {
+                Some(label) if labels.insert(label.clone()) => label,
+                Some(label) => unique_id(&label, &mut taken),
+                None => unique_id(&slugify(&heading.text), &mut taken),
+            };
+            if let Some(key) = heading.key {
+                anchors.insert(key, id.clone());
+            }
+            if let Some(generated) = heading.generated_id {
+                renames.insert(generated, id.clone());
+            }
+            if self.max_level.is_none_or(|max| heading.level <= max) {
+                flat.push(OutlineEntry {
+                    level: heading.level,
+                    text: heading.text,
+                    id,
+                    children: Vec::new(),
+                });
+            }
+        }
+
+        Outline {
+            entries: nest(flat),
+            anchors,
+            renames,
+        }
+    }
+}
+
+/// Nest a flat list of entries by level.
+///
+/// A heading becomes a child of the nearest preceding heading with a lower
+/// level; skipped levels (`=` followed by `===`) nest directly.
+fn nest(flat: Vec<OutlineEntry>) -> Vec<OutlineEntry> {
+    let mut roots = Vec::new();
+    // Open ancestors, innermost last
+    let mut stack: Vec<OutlineEntry> = Vec::new();
+
+    for entry in flat {
+        while stack.last().is_some_and(|open| open.level >= entry.level) {
+            close(&mut stack, &mut roots);
+        }
+        stack.push(entry);
+    }
+    while !stack.is_empty() {
+        close(&mut stack, &mut roots);
+    }
+    roots
+}
+
+fn close(stack: &mut Vec<OutlineEntry>, roots: &mut Vec<OutlineEntry>) {
+    let entry = stack.pop().unwrap();
+    match stack.last_mut() {
+        Some(parent) => parent.children.push(entry),
+        None => roots.push(entry),
+    }
+}
+
+/// De-duplicate `base` against `taken` with a numeric suffix.
+fn unique_id(base: &str, taken: &mut FxHashSet<String>) -> String {
+    let mut id = base.to_string();
+    let mut n = 1;
+    while taken.contains(&id) {
+        id = format!("{base}-{n}");
+        n += 1;
+    }
+    taken.insert(id.clone());
+    id
+}
+
+/// Lowercase alphanumerics, words joined by `-` (`"Getting Started!"` → `getting-started`).
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if (c.is_whitespace() || c == '-' || c == '_') && !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+    if slug.is_empty() {
+        slug.push_str("section");
+    }
+    slug
+}
+
+/// Identifies a heading: its span and the number of earlier headings with
+/// the same span (headings generated by a loop or template share a span).
+pub(crate) type HeadingKey = (Span, usize);
+
+/// Assigns [`HeadingKey`]s to headings visited in document order.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HeadingKeys(FxHashMap<Span, usize>);
+
+impl HeadingKeys {
+    /// Key of the next heading with `span`, or `None` if it is detached.
+    pub(crate) fn next(&mut self, span: Span) -> Option<HeadingKey> {
+        if span.is_detached() {
+            return None;
+        }
+        let count = self.0.entry(span).or_default();
+        *count += 1;
+        Some((span, *count - 1))
+    }
+}
+
+/// Serializer hook writing outline ids onto heading elements.
+///
+/// Created by [`Outline::anchors`]. Headings are counted as they are
+/// serialized, so use a fresh hook for each serialization.
+#[derive(Debug, Clone)]
+pub struct HeadingAnchors<'a> {
+    anchors: &'a FxHashMap<HeadingKey, String>,
+    renames: &'a FxHashMap<String, String>,
+    keys: HeadingKeys,
+}
+
+impl SerializeHook for HeadingAnchors<'_> {
+    fn attrs(&mut self, elem: HtmlElement<'_>, attrs: &mut Vec<(String, String)>) {
+        for (_, value) in attrs.iter_mut().filter(|(name, _)| name == "href") {
+            if let Some(id) = value.strip_prefix('#').and_then(|id| self.renames.get(id)) {
+                *value = format!("#{id}");
+            }
+        }
+
+        if !is_heading(elem) {
+            return;
+        }
+        let Some(id) = self.keys.next(elem.0.span).and_then(|key| self.anchors.get(&key)) else {
+            return;
+        };
+        match attrs.iter_mut().find(|(name, _)| name == "id") {
+            Some((_, value)) => *value = id.clone(),
+            None => attrs.insert(0, ("id".into(), id.clone())),
+        }
+    }
+}
+
+/// `<h2>`–`<h6>`, or `<div role="heading">` for deeper levels.
+pub(crate) fn is_heading(elem: HtmlElement<'_>) -> bool {
+    match elem.tag().as_str() {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => true,
+        "div" => elem.get_attr("role").as_deref() == Some("heading"),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Getting Started!"), "getting-started");
+        assert_eq!(slugify("  API -- v2_beta "), "api-v2-beta");
+        assert_eq!(slugify("Über Größe"), "über-größe");
+        assert_eq!(slugify("?!"), "section");
+    }
+
+    #[test]
+    fn test_builder_nesting_and_ids() {
+        let mut builder = OutlineBuilder::new();
+        builder.push(1, "Setup", None);
+        builder.push(2, "Install", None);
+        builder.push(3, "Linux", None);
+        builder.push(2, "Setup", None);
+        builder.push(1, "Usage", Some("setup-1"));
+        builder.push(3, "Deep", None);
+        let outline = builder.build();
+
+        let ids: Vec<_> = outline.iter().map(|e| e.id.as_str()).collect();
+        // `setup-1` is claimed by a label, so the second "Setup" skips it
+        assert_eq!(ids, ["setup", "install", "linux", "setup-2", "setup-1", "deep"]);
+
+        assert_eq!(outline.entries().len(), 2);
+        assert_eq!(outline.entries()[0].children.len(), 2);
+        assert_eq!(outline.entries()[0].children[0].children[0].text, "Linux");
+        assert_eq!(outline.entries()[1].children[0].text, "Deep");
+    }
+
+    #[test]
+    fn test_builder_max_level() {
+        let mut builder = OutlineBuilder::new().with_max_level(1);
+        builder.push(1, "A", None);
+        builder.push(2, "B", None);
+        builder.push(1, "B", None);
+        let outline = builder.build();
+
+        let ids: Vec<_> = outline.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b-1"]);
+    }
+
+    #[test]
+    fn test_builder_reserve() {
+        let mut builder = OutlineBuilder::new();
+        builder.reserve("intro");
+        builder.push(1, "Intro", None);
+        let outline = builder.build();
+        assert_eq!(outline.entries()[0].id, "intro-1");
+    }
+
+    #[test]
+    fn test_builder_duplicate_labels() {
+        let mut builder = OutlineBuilder::new();
+        builder.push(1, "A", Some("part"));
+        builder.push(1, "B", Some("part"));
+        builder.push(1, "Part 1", None);
+        let outline = builder.build();
+
+        let ids: Vec<_> = outline.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["part", "part-1", "part-1-1"]);
+    }
+}
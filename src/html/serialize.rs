@@ -0,0 +1,734 @@
+//! Configurable HTML serialization.
+//!
+//! [`HtmlSerializer`] writes an [`HtmlDocument`] with the same escaping and
+//! element rules as `typst_html::html`, plus:
+//! - [`HtmlFormat`] - Pretty-printed (configurable indent), compact, or minified output
+//! - [`AttrOrder`] - Source or alphabetical attribute order
+//! - [`SerializeHook`] - Per-node hook to replace or drop nodes and rewrite attributes
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut serializer = HtmlSerializer::new()
+//!     .minify()
+//!     .with_attr_order(AttrOrder::Sorted)
+//!     .with_hook(|node: HtmlNode<'_>| match node.as_frame() {
+//!         Some(frame) => NodeAction::Replace(format!("<img alt=\"{}\">", frame.width())),
+//!         None => NodeAction::Keep,
+//!     });
+//!
+//! let html = serializer.serialize(result.document())?;
+//! ```
+
+use std::fmt::Write;
+
+use super::{HtmlDocument, HtmlElement, HtmlNode};
+use crate::diagnostic::CompileError;
+
+/// Output layout of an [`HtmlSerializer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HtmlFormat {
+    /// Newlines and indentation around block-level elements.
+    ///
+    /// With `indent: 2` the output matches `typst_html::html`.
+    Pretty {
+        /// Spaces per nesting level.
+        indent: usize,
+    },
+    /// No whitespace is added; text is written as-is.
+    Compact,
+    /// Compact, plus collapsed whitespace, dropped inter-block whitespace
+    /// and unquoted attribute values where allowed.
+    ///
+    /// Whitespace inside `<pre>`, `<textarea>`, `<script>` and `<style>`
+    /// is preserved.
+    Minify,
+}
+
+impl Default for HtmlFormat {
+    fn default() -> Self {
+        Self::Pretty { indent: 2 }
+    }
+}
+
+/// Order in which element attributes are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AttrOrder {
+    /// Keep the order in which Typst produced the attributes.
+    #[default]
+    Source,
+    /// Sort attributes by name (stable output for diffing and caching).
+    Sorted,
+}
+
+/// What the serializer should do with a node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeAction {
+    /// Serialize the node normally.
+    Keep,
+    /// Omit the node (and its children).
+    Skip,
+    /// Write the given string verbatim instead of the node.
+    Replace(String),
+}
+
+/// Per-node callbacks invoked while serializing.
+///
+/// Closures `FnMut(HtmlNode<'_>) -> NodeAction` implement this trait.
+///
+/// Introspection tags are never written; returning [`NodeAction::Replace`]
+/// for them is the only way to emit anything in their place.
+pub trait SerializeHook {
+    /// Called for every node below the root, before it is written.
+    ///
+    /// Children of raw text elements (`<script>`, `<style>`) are not passed.
+    fn node(&mut self, _node: HtmlNode<'_>) -> NodeAction {
+        NodeAction::Keep
+    }
+
+    /// Called for every written element to rewrite its attributes in place.
+    ///
+    /// Runs before [`AttrOrder`] is applied.
+    fn attrs(&mut self, _elem: HtmlElement<'_>, _attrs: &mut Vec<(String, String)>) {}
+}
+
+impl<F> SerializeHook for F
+where
+    F: FnMut(HtmlNode<'_>) -> NodeAction,
+{
+    fn node(&mut self, node: HtmlNode<'_>) -> NodeAction {
+        self(node)
+    }
+}
+
+/// Runs both hooks: the first action other than [`NodeAction::Keep`] wins,
+/// and both rewrite attributes (first, then second).
+impl<A: SerializeHook, B: SerializeHook> SerializeHook for (A, B) {
+    fn node(&mut self, node: HtmlNode<'_>) -> NodeAction {
+        match self.0.node(node) {
+            NodeAction::Keep => self.1.node(node),
+            action => action,
+        }
+    }
+
+    fn attrs(&mut self, elem: HtmlElement<'_>, attrs: &mut Vec<(String, String)>) {
+        self.0.attrs(elem, attrs);
+        self.1.attrs(elem, attrs);
+    }
+}
+
+/// Configurable HTML serializer.
+///
+/// Frames are rendered as inline SVG when the `svg` feature is enabled;
+/// without it, a hook must replace or skip them.
+#[derive(Default)]
+pub struct HtmlSerializer<'h> {
+    format: HtmlFormat,
+    attr_order: AttrOrder,
+    hook: Option<Box<dyn SerializeHook + 'h>>,
+}
+
+impl<'h> HtmlSerializer<'h> {
+    /// Create a serializer producing the same output as `typst_html::html`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the output format.
+    pub fn with_format(mut self, format: HtmlFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Pretty-print with `indent` spaces per level.
+    pub fn pretty(self, indent: usize) -> Self {
+        self.with_format(HtmlFormat::Pretty { indent })
+    }
+
+    /// Write without added whitespace.
+    pub fn compact(self) -> Self {
+        self.with_format(HtmlFormat::Compact)
+    }
+
+    /// Write minified output.
+    pub fn minify(self) -> Self {
+        self.with_format(HtmlFormat::Minify)
+    }
+
+    /// Set the attribute order.
+    pub fn with_attr_order(mut self, order: AttrOrder) -> Self {
+        self.attr_order = order;
+        self
+    }
+
+    /// Set the per-node hook.
+    pub fn with_hook(mut self, hook: impl SerializeHook + 'h) -> Self {
+        self.hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Get the output format.
+    pub fn format(&self) -> HtmlFormat {
+        self.format
+    }
+
+    /// Serialize a document to an HTML string.
+    pub fn serialize(&mut self, doc: &HtmlDocument) -> Result<String, CompileError> {
+        let (pretty, indent) = match self.format {
+            HtmlFormat::Pretty { indent } => (true, indent),
+            HtmlFormat::Compact | HtmlFormat::Minify => (false, 0),
+        };
+
+        let mut w = Writer {
+            buf: String::new(),
+            level: 0,
+            indent,
+            pretty,
+            minify: self.format == HtmlFormat::Minify,
+            preserve: 0,
+            attr_order: self.attr_order,
+            hook: self.hook.as_deref_mut(),
+            doc,
+        };
+
+        w.buf.push_str("<!DOCTYPE html>");
+        w.write_indent();
+        w.write_element(doc.root())?;
+        if w.pretty {
+            w.buf.push('\n');
+        }
+        Ok(w.buf)
+    }
+}
+
+impl std::fmt::Debug for HtmlSerializer<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HtmlSerializer")
+            .field("format", &self.format)
+            .field("attr_order", &self.attr_order)
+            .field("hook", &self.hook.is_some())
+            .finish()
+    }
+}
+
+/// Serialization state.
+struct Writer<'w, 'h> {
+    buf: String,
+    level: usize,
+    indent: usize,
+    pretty: bool,
+    minify: bool,
+    /// Depth of whitespace-preserving elements (`<pre>`, `<textarea>`).
+    preserve: usize,
+    attr_order: AttrOrder,
+    hook: Option<&'w mut (dyn SerializeHook + 'h)>,
+    #[cfg_attr(not(feature = "svg"), allow(dead_code))]
+    doc: &'w HtmlDocument,
+}
+
+impl Writer<'_, '_> {
+    /// Write a newline and indent, if pretty printing is enabled.
+    fn write_indent(&mut self) {
+        if self.pretty {
+            self.buf.push('\n');
+            for _ in 0..self.level * self.indent {
+                self.buf.push(' ');
+            }
+        }
+    }
+
+    fn action(&mut self, node: HtmlNode<'_>) -> NodeAction {
+        match &mut self.hook {
+            Some(hook) => hook.node(node),
+            None => NodeAction::Keep,
+        }
+    }
+
+    fn write_node(&mut self, node: HtmlNode<'_>, escape_text: bool) -> Result<(), CompileError> {
+        match self.action(node) {
+            NodeAction::Keep => {}
+            NodeAction::Skip => return Ok(()),
+            NodeAction::Replace(html) => {
+                self.buf.push_str(&html);
+                return Ok(());
+            }
+        }
+
+        match node.0 {
+            typst_html::HtmlNode::Tag(_) => {}
+            typst_html::HtmlNode::Text(text, _) => self.write_text(text, escape_text)?,
+            typst_html::HtmlNode::Element(elem) => self.write_element(HtmlElement(elem))?,
+            typst_html::HtmlNode::Frame(frame) => self.write_frame(frame)?,
+        }
+        Ok(())
+    }
+
+    fn write_text(&mut self, text: &str, escape: bool) -> Result<(), CompileError> {
+        let collapse = self.minify && self.preserve == 0;
+        let mut prev_space = false;
+        for c in text.chars() {
+            if collapse && c.is_ascii_whitespace() {
+                if !prev_space {
+                    self.buf.push(' ');
+                }
+                prev_space = true;
+                continue;
+            }
+            prev_space = false;
+            if escape || !is_valid_in_normal_element_text(c) {
+                write_escape(&mut self.buf, c)?;
+            } else {
+                self.buf.push(c);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_element(&mut self, elem: HtmlElement<'_>) -> Result<(), CompileError> {
+        let tag = elem.tag();
+        self.buf.push('<');
+        self.buf.push_str(&tag);
+
+        let mut attrs = elem.attrs_vec();
+        if let Some(hook) = &mut self.hook {
+            hook.attrs(elem, &mut attrs);
+        }
+        if self.attr_order == AttrOrder::Sorted {
+            attrs.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        for (name, value) in &attrs {
+            self.write_attr(name, value)?;
+        }
+        self.buf.push('>');
+
+        if is_void(&tag) {
+            if !elem.is_empty() {
+                return Err(export_error(format!("HTML void element <{tag}> must not have children")));
+            }
+            return Ok(());
+        }
+
+        // See HTML spec § 13.1.2.5.
+        let preserve = matches!(tag.as_str(), "pre" | "textarea");
+        if preserve && starts_with_newline(elem) {
+            self.buf.push('\n');
+        }
+
+        self.preserve += preserve as usize;
+        if is_raw(&tag) {
+            self.write_raw(elem, &tag)?;
+        } else if is_escapable_raw(&tag) {
+            for child in elem.children() {
+                match child.0 {
+                    typst_html::HtmlNode::Tag(_) => {}
+                    typst_html::HtmlNode::Text(text, _) => self.write_text(text, false)?,
+                    _ => return Err(raw_children_error(&tag)),
+                }
+            }
+        } else if !elem.is_empty() {
+            self.write_children(elem, &tag)?;
+        }
+        self.preserve -= preserve as usize;
+
+        self.buf.push_str("</");
+        self.buf.push_str(&tag);
+        self.buf.push('>');
+        Ok(())
+    }
+
+    fn write_attr(&mut self, name: &str, value: &str) -> Result<(), CompileError> {
+        self.buf.push(' ');
+        self.buf.push_str(name);
+
+        // `<elem attr="">` is equivalent to `<elem attr>`
+        if value.is_empty() {
+            return Ok(());
+        }
+
+        self.buf.push('=');
+        if self.minify && can_unquote(value) {
+            self.buf.push_str(value);
+            return Ok(());
+        }
+
+        self.buf.push('"');
+        for c in value.chars() {
+            if is_valid_in_attribute_value(c) {
+                self.buf.push(c);
+            } else {
+                write_escape(&mut self.buf, c)?;
+            }
+        }
+        self.buf.push('"');
+        Ok(())
+    }
+
+    fn write_children(&mut self, elem: HtmlElement<'_>, tag: &str) -> Result<(), CompileError> {
+        let children: Vec<_> = elem.children().collect();
+        let pretty = self.pretty;
+        let pretty_inside = allows_pretty_inside(tag)
+            && children.iter().any(|node| match node.0 {
+                typst_html::HtmlNode::Element(child) => wants_pretty_around(&child.tag.resolve()),
+                typst_html::HtmlNode::Frame(_) => true,
+                _ => false,
+            });
+
+        self.pretty &= pretty_inside;
+        let mut indent = self.pretty;
+
+        self.level += 1;
+        for (i, &child) in children.iter().enumerate() {
+            let pretty_around = match child.0 {
+                typst_html::HtmlNode::Tag(_) => {
+                    // Tags are only written if a hook replaces them
+                    self.write_node(child, elem.0.pre_span)?;
+                    continue;
+                }
+                typst_html::HtmlNode::Element(e) => {
+                    self.pretty && wants_pretty_around(&e.tag.resolve())
+                }
+                typst_html::HtmlNode::Text(text, _) => {
+                    if self.minify
+                        && self.preserve == 0
+                        && allows_pretty_inside(tag)
+                        && text.chars().all(|c| c.is_ascii_whitespace())
+                        && nearest_is_block(children[..i].iter().rev())
+                        && nearest_is_block(children[i + 1..].iter())
+                    {
+                        continue;
+                    }
+                    false
+                }
+                typst_html::HtmlNode::Frame(_) => false,
+            };
+
+            if std::mem::take(&mut indent) || pretty_around {
+                self.write_indent();
+            }
+            self.write_node(child, elem.0.pre_span)?;
+            indent = pretty_around;
+        }
+        self.level -= 1;
+
+        self.write_indent();
+        self.pretty = pretty;
+        Ok(())
+    }
+
+    fn write_raw(&mut self, elem: HtmlElement<'_>, tag: &str) -> Result<(), CompileError> {
+        let mut text = String::new();
+        for child in elem.children() {
+            match child.0 {
+                typst_html::HtmlNode::Tag(_) => {}
+                typst_html::HtmlNode::Text(piece, _) => {
+                    if let Some(c) = piece.chars().find(|&c| !is_w3c_text_char(c)) {
+                        return Err(unencodable(c));
+                    }
+                    text.push_str(piece);
+                }
+                _ => return Err(raw_children_error(tag)),
+            }
+        }
+
+        if let Some(closing) = find_closing_tag(&text, tag) {
+            return Err(export_error(format!(
+                "HTML raw text element cannot contain its own closing tag `{closing}`"
+            )));
+        }
+
+        let is_js = !elem.attrs().any(|(k, v)| k == "type" && v != "text/javascript");
+        match tag {
+            _ if !self.pretty => self.buf.push_str(&text),
+            // Template literals can be multi-line, so indent may change semantics
+            "script" if is_js && text.contains('`') => {
+                self.buf.push('\n');
+                self.buf.push_str(&text);
+                self.write_indent();
+            }
+            "script" if is_js => self.write_indented_lines(&text),
+            "style" => self.write_indented_lines(&text),
+            _ => self.buf.push_str(&text),
+        }
+        Ok(())
+    }
+
+    fn write_indented_lines(&mut self, text: &str) {
+        self.level += 1;
+        for line in text.lines() {
+            self.write_indent();
+            self.buf.push_str(line);
+        }
+        self.level -= 1;
+        self.write_indent();
+    }
+
+    #[cfg(feature = "svg")]
+    fn write_frame(&mut self, frame: &typst_html::HtmlFrame) -> Result<(), CompileError> {
+        let svg = self.doc.render_frame_svg(&super::HtmlFrame(frame));
+        self.buf.push_str(&svg);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "svg"))]
+    fn write_frame(&mut self, _frame: &typst_html::HtmlFrame) -> Result<(), CompileError> {
+        Err(export_error(
+            "HTML frames require the `svg` feature or a hook that replaces them",
+        ))
+    }
+}
+
+/// Whether the nearest non-tag sibling is a block element, or there is none.
+fn nearest_is_block<'a>(mut siblings: impl Iterator<Item = &'a HtmlNode<'a>>) -> bool {
+    match siblings.find(|n| !n.is_tag()).map(|n| n.0) {
+        None => true,
+        Some(typst_html::HtmlNode::Element(e)) => wants_pretty_around(&e.tag.resolve()),
+        Some(_) => false,
+    }
+}
+
+/// Whether the first character in the element is a newline.
+fn starts_with_newline(elem: HtmlElement<'_>) -> bool {
+    for child in elem.children() {
+        match child.0 {
+            typst_html::HtmlNode::Tag(_) => {}
+            typst_html::HtmlNode::Text(text, _) => return text.starts_with(['\n', '\r']),
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Find a closing sequence for the given tag in raw text (HTML spec § 13.1.2.6).
+fn find_closing_tag<'t>(text: &'t str, tag: &str) -> Option<&'t str> {
+    let len = tag.len();
+    text.match_indices("</").find_map(|(i, _)| {
+        let rest = &text[i + 2..];
+        let disallowed = rest.len() >= len
+            && rest[..len].eq_ignore_ascii_case(tag)
+            && rest[len..].starts_with(['\t', '\n', '\u{c}', '\r', ' ', '>', '/']);
+        disallowed.then(|| &text[i..i + 2 + len])
+    })
+}
+
+/// Whether an attribute value can be written without quotes.
+fn can_unquote(value: &str) -> bool {
+    value.chars().all(|c| {
+        is_valid_in_attribute_value(c)
+            && !c.is_ascii_whitespace()
+            && !matches!(c, '\'' | '=' | '<' | '>' | '`')
+    })
+}
+
+/// Escape a character.
+fn write_escape(buf: &mut String, c: char) -> Result<(), CompileError> {
+    // See <https://html.spec.whatwg.org/multipage/syntax.html#syntax-charref>
+    match c {
+        '&' => buf.push_str("&amp;"),
+        '<' => buf.push_str("&lt;"),
+        '>' => buf.push_str("&gt;"),
+        '"' => buf.push_str("&quot;"),
+        '\'' => buf.push_str("&apos;"),
+        c if is_w3c_text_char(c) && c != '\r' => write!(buf, "&#x{:x};", c as u32).unwrap(),
+        _ => return Err(unencodable(c)),
+    }
+    Ok(())
+}
+
+#[cold]
+fn unencodable(c: char) -> CompileError {
+    export_error(format!("the character {c:?} cannot be encoded in HTML"))
+}
+
+#[cold]
+fn raw_children_error(tag: &str) -> CompileError {
+    export_error(format!("HTML raw text element <{tag}> cannot have non-text children"))
+}
+
+fn export_error(message: impl Into<String>) -> CompileError {
+    CompileError::html_export(message)
+}
+
+// =============================================================================
+// Element and Character Classes (mirroring typst-html, which keeps them private)
+// =============================================================================
+
+fn is_void(tag: &str) -> bool {
+    matches!(
+        tag,
+        "area" | "base" | "br" | "col" | "embed" | "hr" | "img" | "input" | "link" | "meta"
+            | "source" | "track" | "wbr"
+    )
+}
+
+fn is_raw(tag: &str) -> bool {
+    matches!(tag, "script" | "style")
+}
+
+fn is_escapable_raw(tag: &str) -> bool {
+    matches!(tag, "textarea" | "title")
+}
+
+fn is_metadata(tag: &str) -> bool {
+    matches!(
+        tag,
+        "base" | "link" | "meta" | "noscript" | "script" | "style" | "template" | "title"
+    )
+}
+
+fn is_block_by_default(tag: &str) -> bool {
+    matches!(
+        tag,
+        "html" | "head" | "body" | "article" | "aside" | "h1" | "h2" | "h3" | "h4" | "h5"
+            | "h6" | "hgroup" | "nav" | "section" | "dd" | "dl" | "dt" | "menu" | "ol" | "ul"
+            | "address" | "blockquote" | "dialog" | "div" | "fieldset" | "figure"
+            | "figcaption" | "footer" | "form" | "header" | "hr" | "legend" | "main" | "p"
+            | "pre" | "search"
+    )
+}
+
+fn is_tabular_by_default(tag: &str) -> bool {
+    matches!(
+        tag,
+        "table" | "thead" | "tbody" | "tfoot" | "tr" | "th" | "td" | "caption" | "col"
+            | "colgroup"
+    )
+}
+
+/// Whether newlines may be added at the start and end of the element's contents.
+fn allows_pretty_inside(tag: &str) -> bool {
+    (is_block_by_default(tag) && tag != "pre") || is_tabular_by_default(tag) || tag == "li"
+}
+
+/// Whether newlines should be added around the element if the parent allows it.
+fn wants_pretty_around(tag: &str) -> bool {
+    allows_pretty_inside(tag) || is_metadata(tag) || tag == "pre"
+}
+
+const fn is_valid_in_attribute_value(c: char) -> bool {
+    match c {
+        '&' | '"' => false,
+        c => is_w3c_text_char(c),
+    }
+}
+
+const fn is_valid_in_normal_element_text(c: char) -> bool {
+    match c {
+        '&' | '<' => false,
+        c => is_w3c_text_char(c),
+    }
+}
+
+const fn is_w3c_text_char(c: char) -> bool {
+    match c {
+        // Non-characters
+        '\u{fdd0}'..='\u{fdef}' => false,
+        c if c as u32 & 0xfffe == 0xfffe && c as u32 <= 0x10ffff => false,
+        // Control characters, except whitespace
+        '\u{00}'..='\u{1f}' | '\u{7f}'..='\u{9f}' => c.is_ascii_whitespace(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::compile_source;
+
+    const SOURCE: &str = r#"= Title
+Some *bold*   and _emph_ text.
+
+#html.elem("div", attrs: (id: "box", class: "a b", data-x: ""))[
+  #html.p[One]
+  #html.p[Two]
+]
+
+```
+keep    spaces
+```
+
+#html.elem("style", "p  { color: red; }")"#;
+
+    #[test]
+    fn test_default_matches_typst() {
+        let doc = compile_source(SOURCE).into_document();
+        let expected = typst_html::html(doc.as_inner()).unwrap();
+        assert_eq!(HtmlSerializer::new().serialize(&doc).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "svg")]
+    fn test_frames_match_typst() {
+        let doc = compile_source("#html.div[#html.frame(rect(width: 4pt))]").into_document();
+        let expected = typst_html::html(doc.as_inner()).unwrap();
+        assert_eq!(HtmlSerializer::new().serialize(&doc).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_pretty_indent() {
+        let doc = compile_source(SOURCE).into_document();
+        let html = HtmlSerializer::new().pretty(4).serialize(&doc).unwrap();
+        assert!(html.contains("\n    <head>"));
+        assert!(html.contains("\n            <p>One</p>"));
+    }
+
+    #[test]
+    fn test_minify() {
+        let doc = compile_source(SOURCE).into_document();
+        let html = HtmlSerializer::new().minify().serialize(&doc).unwrap();
+
+        assert!(!html.contains("\n  <"));
+        assert!(html.contains(r#"<div id=box class="a b" data-x><p>One</p><p>Two</p></div>"#));
+        // Whitespace in `<pre>` and `<style>` is preserved
+        assert!(html.contains("Some <strong>bold</strong> and"));
+        assert!(html.contains("<code>keep    spaces</code>"));
+        assert!(html.contains("<style>p  { color: red; }</style>"));
+    }
+
+    #[test]
+    fn test_sorted_attrs() {
+        let doc = compile_source(SOURCE).into_document();
+        let html = HtmlSerializer::new()
+            .compact()
+            .with_attr_order(AttrOrder::Sorted)
+            .serialize(&doc)
+            .unwrap();
+        assert!(html.contains(r#"<div class="a b" data-x id="box">"#));
+    }
+
+    #[test]
+    fn test_hook_replace_skip_and_attrs() {
+        struct Hook<'a>(&'a mut usize);
+
+        impl SerializeHook for Hook<'_> {
+            fn node(&mut self, node: HtmlNode<'_>) -> NodeAction {
+                if node.is_frame() {
+                    *self.0 += 1;
+                    return NodeAction::Replace("<img src=\"frame.svg\">".into());
+                }
+                match node.as_element() {
+                    Some(e) if e.tag() == "style" => NodeAction::Skip,
+                    _ => NodeAction::Keep,
+                }
+            }
+
+            fn attrs(&mut self, elem: HtmlElement<'_>, attrs: &mut Vec<(String, String)>) {
+                if elem.id().as_deref() == Some("box") {
+                    attrs.retain(|(k, _)| k != "data-x");
+                    attrs.push(("role".into(), "note".into()));
+                }
+            }
+        }
+
+        let doc = compile_source(&format!("{SOURCE}\n#html.frame(rect(width: 1pt))")).into_document();
+        let mut frames = 0;
+        let html = HtmlSerializer::new()
+            .with_hook(Hook(&mut frames))
+            .serialize(&doc)
+            .unwrap();
+
+        assert_eq!(frames, 1);
+        assert!(html.contains(r#"<img src="frame.svg">"#));
+        assert!(!html.contains("<style>"));
+        assert!(html.contains(r#"<div id="box" class="a b" role="note">"#));
+    }
+}
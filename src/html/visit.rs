@@ -0,0 +1,258 @@
+//! HTML tree traversal.
+//!
+//! - [`Visitor`] - Depth-first walk with enter/leave callbacks and early exit
+//! - [`Descendants`] - Depth-first (document order) node iterator
+//! - [`BreadthFirst`] - Breadth-first (level order) node iterator
+//! - [`collect_frames`] - All frames of a document in document order
+
+use std::collections::VecDeque;
+use std::ops::ControlFlow;
+
+use super::{HtmlDocument, HtmlElement, HtmlFrame, HtmlNode};
+
+/// Callbacks for walking an HTML tree.
+///
+/// Return `ControlFlow::Break(())` from any callback to stop the walk early.
+///
+/// # Example
+///
+/// ```ignore
+/// struct Depth { current: usize, max: usize }
+///
+/// impl<'a> Visitor<'a> for Depth {
+///     fn enter(&mut self, node: HtmlNode<'a>) -> ControlFlow<()> {
+///         if node.is_element() {
+///             self.current += 1;
+///             self.max = self.max.max(self.current);
+///         }
+///         ControlFlow::Continue(())
+///     }
+///
+///     fn leave(&mut self, _elem: HtmlElement<'a>) -> ControlFlow<()> {
+///         self.current -= 1;
+///         ControlFlow::Continue(())
+///     }
+/// }
+///
+/// doc.root().walk(&mut Depth { current: 0, max: 0 });
+/// ```
+pub trait Visitor<'a> {
+    /// Called for every node before its children are visited.
+    fn enter(&mut self, node: HtmlNode<'a>) -> ControlFlow<()>;
+
+    /// Called for every element after all its children have been visited.
+    ///
+    /// Not called during breadth-first walks.
+    fn leave(&mut self, _elem: HtmlElement<'a>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+impl<'a, F> Visitor<'a> for F
+where
+    F: FnMut(HtmlNode<'a>) -> ControlFlow<()>,
+{
+    fn enter(&mut self, node: HtmlNode<'a>) -> ControlFlow<()> {
+        self(node)
+    }
+}
+
+/// Walk the children of `elem` depth-first, calling `enter`/`leave`.
+pub(crate) fn walk_depth_first<'a, V: Visitor<'a> + ?Sized>(
+    elem: HtmlElement<'a>,
+    visitor: &mut V,
+) -> ControlFlow<()> {
+    for child in elem.children() {
+        visitor.enter(child)?;
+        if let Some(child_elem) = child.as_element() {
+            walk_depth_first(child_elem, visitor)?;
+            visitor.leave(child_elem)?;
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+/// Walk the descendants of `elem` breadth-first, calling `enter` only.
+pub(crate) fn walk_breadth_first<'a, V: Visitor<'a> + ?Sized>(
+    elem: HtmlElement<'a>,
+    visitor: &mut V,
+) -> ControlFlow<()> {
+    for node in BreadthFirst::new(elem) {
+        visitor.enter(node)?;
+    }
+    ControlFlow::Continue(())
+}
+
+/// Depth-first iterator over all descendant nodes (document order).
+///
+/// Created by [`HtmlElement::descendants`].
+pub struct Descendants<'a> {
+    stack: Vec<std::slice::Iter<'a, typst_html::HtmlNode>>,
+}
+
+impl<'a> Descendants<'a> {
+    pub(crate) fn new(elem: HtmlElement<'a>) -> Self {
+        Self {
+            stack: vec![elem.0.children.iter()],
+        }
+    }
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = HtmlNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let top = self.stack.last_mut()?;
+            match top.next() {
+                Some(node) => {
+                    if let typst_html::HtmlNode::Element(elem) = node {
+                        self.stack.push(elem.children.iter());
+                    }
+                    return Some(HtmlNode(node));
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Breadth-first iterator over all descendant nodes (level order).
+///
+/// Created by [`HtmlElement::descendants_breadth_first`].
+pub struct BreadthFirst<'a> {
+    queue: VecDeque<&'a typst_html::HtmlNode>,
+}
+
+impl<'a> BreadthFirst<'a> {
+    pub(crate) fn new(elem: HtmlElement<'a>) -> Self {
+        Self {
+            queue: elem.0.children.iter().collect(),
+        }
+    }
+}
+
+impl<'a> Iterator for BreadthFirst<'a> {
+    type Item = HtmlNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        if let typst_html::HtmlNode::Element(elem) = node {
+            self.queue.extend(elem.children.iter());
+        }
+        Some(HtmlNode(node))
+    }
+}
+
+/// Collect all frames in the document, in document order.
+///
+/// # Example
+///
+/// ```ignore
+/// let frames = collect_frames(&doc);
+/// let svgs = doc.render_frames(&frames);
+/// ```
+pub fn collect_frames(doc: &HtmlDocument) -> Vec<HtmlFrame<'_>> {
+    doc.root()
+        .descendants()
+        .filter_map(|node| node.as_frame())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::compile_source;
+
+    const SOURCE: &str = r#"#html.div(id: "outer")[
+  #html.p(id: "first")[One #html.span[inner]]
+  #html.p(id: "second")[Two]
+]"#;
+
+    #[test]
+    fn test_descendants_order() {
+        let doc = compile_source(SOURCE).into_document();
+        let div = doc.root().find_by_id("outer").unwrap();
+
+        let tags: Vec<_> = div
+            .descendants()
+            .filter_map(|n| n.as_element())
+            .map(|e| e.tag())
+            .collect();
+        assert_eq!(tags, ["p", "span", "p"]);
+
+        let tags: Vec<_> = div
+            .descendants_breadth_first()
+            .filter_map(|n| n.as_element())
+            .map(|e| e.tag())
+            .collect();
+        assert_eq!(tags, ["p", "p", "span"]);
+    }
+
+    #[test]
+    fn test_find_by_tag_and_id() {
+        let doc = compile_source(SOURCE).into_document();
+        let root = doc.root();
+
+        let ids: Vec<_> = root.find_by_tag("p").filter_map(|e| e.id()).collect();
+        assert_eq!(ids, ["first", "second"]);
+        assert_eq!(root.find_by_id("second").unwrap().tag(), "p");
+        assert!(root.find_by_id("missing").is_none());
+    }
+
+    #[test]
+    fn test_visitor_enter_leave_and_break() {
+        struct Recorder(Vec<String>);
+
+        impl<'a> Visitor<'a> for Recorder {
+            fn enter(&mut self, node: HtmlNode<'a>) -> ControlFlow<()> {
+                if let Some(elem) = node.as_element() {
+                    self.0.push(format!("+{}", elem.tag()));
+                }
+                ControlFlow::Continue(())
+            }
+
+            fn leave(&mut self, elem: HtmlElement<'a>) -> ControlFlow<()> {
+                self.0.push(format!("-{}", elem.tag()));
+                if elem.tag() == "span" {
+                    return ControlFlow::Break(());
+                }
+                ControlFlow::Continue(())
+            }
+        }
+
+        let doc = compile_source(SOURCE).into_document();
+        let div = doc.root().find_by_id("outer").unwrap();
+
+        let mut recorder = Recorder(Vec::new());
+        assert!(div.walk(&mut recorder).is_break());
+        assert_eq!(recorder.0, ["+p", "+span", "-span"]);
+    }
+
+    #[test]
+    fn test_closure_visitor_breadth_first() {
+        let doc = compile_source(SOURCE).into_document();
+        let mut count = 0;
+        let flow = doc.root().walk_breadth_first(&mut |node: HtmlNode<'_>| {
+            if node.is_element() {
+                count += 1;
+            }
+            ControlFlow::Continue(())
+        });
+        assert!(flow.is_continue());
+        assert!(count >= 4);
+    }
+
+    #[test]
+    fn test_collect_frames() {
+        let doc = compile_source(
+            r#"#html.frame(rect(width: 1pt))
+#html.div[#html.frame(rect(width: 2pt))]"#,
+        ).into_document();
+        let frames = collect_frames(&doc);
+        assert_eq!(frames.len(), 2);
+        assert!(frames[0].width() < frames[1].width());
+    }
+}
@@ -6,25 +6,39 @@
 
 // Compilation (Builder API)
 pub use crate::process::compile::{CompileResult, Compiler, MainPath, RootPath, SingleCompiler};
-pub use crate::process::{AccessedDeps, CompileSession, WithInputs};
+pub use crate::process::{
+    AccessedDeps, BatchReport, CompileSession, CompileStats, WithInputs, WithInterrupt,
+    WithTimestamp,
+};
+pub use crate::process::deps::DependencyGraph;
+pub use crate::process::project::{Project, ProjectUpdate};
+pub use crate::process::search::{SearchIndex, SearchOptions, Searchable};
 #[cfg(feature = "batch")]
 pub use crate::process::batch::Batcher;
 #[cfg(feature = "batch")]
+pub use crate::process::{pretty_html_path, BatchEvent, BuildManifest, BuildStatus, FailurePolicy, WithParallelism};
+#[cfg(feature = "batch")]
 pub use crate::world::FileSnapshot;
+#[cfg(feature = "pdf")]
+pub use crate::process::paged::PagedResult;
 
 
 // Fast Scanning (5-20x faster than compile)
 #[cfg(feature = "scan")]
 pub use crate::process::scan::{
-    extract, Extractor, Heading, HeadingExtractor, Link, LinkExtractor, LinkSource,
-    MetadataExtractor, ScanResult, Scanner,
+    extract, Bibliography, BibliographyExtractor, DynExtractor, ExtractorSet, IntoJson, Citation, CitationExtractor, CitationSource,
+    Extractor, Figure, FigureExtractor, Footnote, FootnoteExtractor, Heading, HeadingExtractor,
+    Image, ImageExtractor, LabelExtractor, Labeled, Link, LinkExtractor, LinkSource,
+    MetadataExtractor, Raw, RawExtractor, ScanResult, Scanner,
 };
+#[cfg(feature = "scan")]
+pub use crate::process::syntax::{SyntaxMetadata, SyntaxScan};
 
 // Diagnostics
 pub use crate::diagnostic::{
     CompileError, DiagnosticFilter, DiagnosticInfo, DiagnosticOptions, DiagnosticSeverity,
     DiagnosticSummary, Diagnostics, DisplayStyle, FilterType, PackageKind, SourceDiagnostic,
-    SourceLine, TraceInfo,
+    SourceLine, SourceLocation, SpanResolver, TraceInfo,
 };
 
 // VFS & VPS
@@ -42,8 +56,8 @@ pub use crate::resource::library::{create_library_with_inputs, GLOBAL_LIBRARY};
 
 // World
 pub use crate::world::{
-    clear_thread_local_cache, normalize_path, CacheStrategy, FontStrategy, LibraryStrategy,
-    LocalCache, TypstWorld, WorldBuilder,
+    clear_thread_local_cache, normalize_path, source_date_epoch, CacheStrategy, CancellationToken, FileStats,
+    FontStrategy, LibraryStrategy, LocalCache, TypstWorld, WorldBuilder,
 };
 
 // Package
@@ -56,7 +70,13 @@ pub use crate::resource::warmup;
 pub use crate::codegen::{DictBuilder, Inputs, ToTypst, array, array_raw, dict, dict_raw, dict_sparse};
 
 // HTML types (stable API)
-pub use crate::html::{HtmlDocument, HtmlElement, HtmlFrame, HtmlNode, NodeKind};
+pub use crate::html::{
+    collect_frames, AttrOrder, HtmlDocument, HtmlElement, HtmlFormat, HtmlFrame, HtmlNode,
+    HtmlSerializer, NodeAction, NodeKind, Outline, OutlineBuilder, OutlineEntry, SerializeHook,
+    Visitor,
+};
+#[cfg(feature = "svg")]
+pub use crate::html::{FrameAsset, FrameCache, FrameCacheStats, FrameMode, HtmlOutput};
 
 
 
@@ -66,4 +86,8 @@ pub mod unstable {
     pub use typst_html;
     #[cfg(feature = "svg")]
     pub use typst_svg;
+    #[cfg(feature = "pdf")]
+    pub use typst_pdf;
+    #[cfg(feature = "png")]
+    pub use typst_render;
 }
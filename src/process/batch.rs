@@ -25,15 +25,29 @@
 
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 
+use parking_lot::{Condvar, Mutex};
+use rustc_hash::FxHashMap;
 use typst::foundations::Dict;
 
 use crate::codegen::json_to_simple_value;
 use crate::diagnostic::CompileError;
-use crate::world::{FileSnapshot, SnapshotConfig, TypstWorld};
+use crate::world::{join_in_root, normalize_path, FileSnapshot, SnapshotConfig, TypstWorld, WorldBuilder};
+#[cfg(feature = "svg")]
+use crate::html::{FrameCache, FrameMode};
 
+use super::build::{write_if_changed, BuildEntry, BuildManifest, BuildStatus};
 use super::compile::{compile_with_world, CompileResult};
+use super::events::{BatchEvent, Observer, Outcome, Task};
+use super::stats::BatchReport;
 use super::inputs::WithInputs;
+use super::interrupt::{InterruptSettings, WithInterrupt};
+use super::parallel::{ParallelSettings, WithParallelism};
+use super::policy::{ErrorBudget, FailurePolicy};
+use super::timestamp::{TimestampSettings, WithTimestamp};
+#[cfg(feature = "pdf")]
+use super::paged::{compile_paged_with_world, PagedResult};
 #[cfg(feature = "scan")]
 use super::scan::{scan_impl, ScanResult};
 
@@ -47,7 +61,17 @@ pub struct Batcher<'a> {
     inputs: Option<Dict>,
     pub(crate) preludes: Vec<String>,
     pub(crate) postludes: Vec<String>,
+    pub(crate) interrupt: InterruptSettings,
+    pub(crate) timestamp: TimestampSettings,
+    parallel: ParallelSettings,
+    policy: FailurePolicy,
+    observer: Observer,
     snapshot: Option<Arc<FileSnapshot>>,
+    #[cfg(feature = "svg")]
+    frame_cache: Option<Arc<FrameCache>>,
+    /// Frame mode and asset directory (relative to the output) for `build_to`.
+    #[cfg(feature = "svg")]
+    frame_output: Option<(FrameMode, PathBuf)>,
 }
 
 impl<'a> WithInputs for Batcher<'a> {
@@ -56,6 +80,24 @@ impl<'a> WithInputs for Batcher<'a> {
     }
 }
 
+impl<'a> WithInterrupt for Batcher<'a> {
+    fn interrupt_mut(&mut self) -> &mut InterruptSettings {
+        &mut self.interrupt
+    }
+}
+
+impl<'a> WithTimestamp for Batcher<'a> {
+    fn timestamp_mut(&mut self) -> &mut TimestampSettings {
+        &mut self.timestamp
+    }
+}
+
+impl<'a> WithParallelism for Batcher<'a> {
+    fn parallel_mut(&mut self) -> &mut ParallelSettings {
+        &mut self.parallel
+    }
+}
+
 impl<'a> Batcher<'a> {
     /// Create a new batcher with the given root directory.
     pub fn new(root: &'a Path) -> Self {
@@ -64,7 +106,16 @@ impl<'a> Batcher<'a> {
             inputs: None,
             preludes: Vec::new(),
             postludes: Vec::new(),
+            interrupt: InterruptSettings::default(),
+            timestamp: TimestampSettings::default(),
+            parallel: ParallelSettings::default(),
+            policy: FailurePolicy::default(),
+            observer: Observer::default(),
             snapshot: None,
+            #[cfg(feature = "svg")]
+            frame_cache: None,
+            #[cfg(feature = "svg")]
+            frame_output: None,
         }
     }
 
@@ -88,6 +139,58 @@ impl<'a> Batcher<'a> {
         self
     }
 
+    /// Share a frame cache across all documents compiled by this batcher.
+    ///
+    /// Every successful [`CompileResult`] gets the cache attached, so
+    /// `html_with()` renders repeated frames (e.g., identical formulas on
+    /// different pages) once per batch.
+    #[cfg(feature = "svg")]
+    pub fn with_frame_cache(mut self, cache: Arc<FrameCache>) -> Self {
+        self.frame_cache = Some(cache);
+        self
+    }
+
+    /// Write frames per `mode` in [`build_to`](Self::build_to).
+    ///
+    /// For [`FrameMode::ExternalSvg`], frame files go to `assets_dir`
+    /// (relative to the output directory), which should match the mode's
+    /// URL prefix. Without this setting, frames are inlined as SVG.
+    #[cfg(feature = "svg")]
+    pub fn with_frame_output(mut self, mode: FrameMode, assets_dir: impl Into<PathBuf>) -> Self {
+        self.frame_output = Some((mode, assets_dir.into()));
+        self
+    }
+
+    /// Stop compiling after errors (see [`FailurePolicy`]).
+    ///
+    /// Applies to each batch call separately. Files that are not compiled
+    /// return [`CompileError::Skipped`], so results still line up with paths.
+    pub fn with_failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Report progress events from snapshot loading and all batch calls.
+    ///
+    /// The callback may run on several threads at once.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let done = AtomicUsize::new(0);
+    /// let batcher = Batcher::new(root).with_observer(move |event| match event {
+    ///     BatchEvent::CompileFinished { path, duration, ok: false, .. } => {
+    ///         eprintln!("failed: {} ({duration:?})", path.display())
+    ///     }
+    ///     event if event.is_done() => bar.inc(1),
+    ///     _ => {}
+    /// });
+    /// ```
+    pub fn with_observer(mut self, observer: impl Fn(&BatchEvent<'_>) + Send + Sync + 'static) -> Self {
+        self.observer = Observer::new(observer);
+        self
+    }
+
     /// Pre-build a snapshot from files for efficient multi-phase compilation.
     ///
     /// The snapshot caches all files and their imports, enabling lock-free
@@ -120,12 +223,12 @@ impl<'a> Batcher<'a> {
         let path_bufs: Vec<PathBuf> = paths.iter().map(|p| p.as_ref().to_path_buf()).collect();
 
         // Build snapshot with prelude/postlude injection
-        let config = SnapshotConfig {
-            prelude: self.build_prelude_opt(),
-            postlude: self.build_postlude_opt(),
+        let config = self.snapshot_config()?;
+        let on_load = |path: &Path| {
+            on_each(path);
+            self.observer.emit(BatchEvent::SnapshotLoaded { path });
         };
-
-        let snapshot = Arc::new(FileSnapshot::build_with_config(&path_bufs, self.root, &config, on_each)?);
+        let snapshot = Arc::new(FileSnapshot::build_with_config(&path_bufs, self.root, &config, on_load)?);
         self.snapshot = Some(snapshot);
 
         Ok(self)
@@ -173,25 +276,18 @@ impl<'a> Batcher<'a> {
         &self,
         paths: &[P],
     ) -> Result<Vec<Result<ScanResult, CompileError>>, CompileError> {
-        use rayon::prelude::*;
-
         if paths.is_empty() {
             return Ok(vec![]);
         }
 
         let snapshot = self.get_or_build_snapshot(paths)?;
 
-        // Scan in parallel with lock-free snapshot access
-        let results: Vec<_> = paths
-            .par_iter()
-            .map(|path| {
-                let path = path.as_ref();
-                let world = self.build_world(path, &snapshot);
-                scan_impl(&world)
-            })
-            .collect();
+        let run = self.run(Task::Scan);
 
-        Ok(results)
+        // Scan in parallel with lock-free snapshot access
+        self.parallel.map_paths(paths, |index, path| {
+            run.file(index, path, || scan_impl(self.build_world(path, &snapshot)))
+        })
     }
 
     /// Compile multiple files in parallel.
@@ -209,8 +305,8 @@ impl<'a> Batcher<'a> {
 
     /// Compile multiple files in parallel with callback for each file.
     ///
-    /// Like `batch_compile`, but invokes the callback once per file compiled.
-    /// Useful for progress tracking.
+    /// Like `batch_compile`, but invokes the callback once per file compiled
+    /// (or skipped by the failure policy). Useful for progress tracking.
     pub fn batch_compile_each<P, F>(
         &self,
         paths: &[P],
@@ -220,27 +316,203 @@ impl<'a> Batcher<'a> {
         P: AsRef<Path> + Sync,
         F: Fn(&Path) + Sync,
     {
-        use rayon::prelude::*;
-
         if paths.is_empty() {
             return Ok(vec![]);
         }
 
         let snapshot = self.get_or_build_snapshot(paths)?;
 
+        let run = self.run(Task::Compile);
+
         // Compile in parallel with lock-free snapshot access
-        let results: Vec<_> = paths
-            .par_iter()
-            .map(|path| {
-                let path = path.as_ref();
-                let world = self.build_world(path, &snapshot);
-                let result = compile_with_world(&world);
-                on_each(path);
-                result
-            })
-            .collect();
+        self.parallel.map_paths(paths, |index, path| {
+            let result = run.file(index, path, || {
+                self.finish(compile_with_world(&self.build_world(path, &snapshot)))
+            });
+            on_each(path);
+            result
+        })
+    }
 
-        Ok(results)
+    /// Compile multiple files in parallel, handing each result to `sink` as
+    /// soon as it finishes.
+    ///
+    /// Unlike `batch_compile`, results are not collected: each one is
+    /// dropped when `sink` returns, so only the documents currently being
+    /// compiled or handled are held in memory. `sink` receives the input
+    /// index (results arrive in completion order, not input order), the
+    /// path and the result, and may be called from several threads at once.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// batcher.batch_compile_stream(&files, |index, path, result| {
+    ///     match result.and_then(|r| r.html()) {
+    ///         Ok(html) => std::fs::write(out_path(path), html).unwrap(),
+    ///         Err(e) => errors.lock().unwrap().push((index, e)),
+    ///     }
+    /// })?;
+    ///
+    /// // Or hand results to a single writer thread
+    /// let (tx, rx) = std::sync::mpsc::channel();
+    /// std::thread::scope(|s| {
+    ///     s.spawn(|| for (index, result) in rx { write(index, result) });
+    ///     batcher.batch_compile_stream(&files, |index, _, result| tx.send((index, result)).unwrap())
+    /// })?;
+    /// ```
+    pub fn batch_compile_stream<P, F>(&self, paths: &[P], sink: F) -> Result<(), CompileError>
+    where
+        P: AsRef<Path> + Sync,
+        F: Fn(usize, &Path, Result<CompileResult, CompileError>) + Sync,
+    {
+        if paths.is_empty() {
+            return Ok(());
+        }
+
+        let snapshot = self.get_or_build_snapshot(paths)?;
+
+        let run = self.run(Task::Compile);
+
+        self.parallel.map_paths(paths, |index, path| {
+            run.file_then(
+                index,
+                path,
+                || self.finish(compile_with_world(&self.build_world(path, &snapshot))),
+                |result| sink(index, path, result),
+            );
+        })?;
+        Ok(())
+    }
+
+    /// Compile files and write their HTML into `out_dir`.
+    ///
+    /// `path_mapper` maps each source path (relative to the root) to an
+    /// output path relative to `out_dir`, e.g. with
+    /// [`pretty_html_path`](super::build::pretty_html_path). Pages are
+    /// written as soon as they compile (see `batch_compile_stream`):
+    /// - atomically, via a temporary file renamed over the target
+    /// - only if the content differs from the existing file, so file
+    ///   watchers and sync tools see real changes only
+    ///
+    /// A mapped path that is absolute or contains `..` would escape
+    /// `out_dir`; such pages are not written and reported as failed.
+    ///
+    /// Failed pages keep their previous output. Frame files from
+    /// [`with_frame_output`](Self::with_frame_output) are written before
+    /// the pages that reference them.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let manifest = batcher.build_to(&files, "public", |path| {
+    ///     pretty_html_path(path.strip_prefix("content").unwrap_or(path))
+    /// })?;
+    /// println!("{} written, {} unchanged", manifest.written().count(), manifest.unchanged().count());
+    /// for (entry, err) in manifest.failed() {
+    ///     eprintln!("{}: {err}", entry.source.display());
+    /// }
+    /// ```
+    pub fn build_to<P, F>(
+        &self,
+        paths: &[P],
+        out_dir: impl AsRef<Path>,
+        path_mapper: F,
+    ) -> Result<BuildManifest, CompileError>
+    where
+        P: AsRef<Path> + Sync,
+        F: Fn(&Path) -> PathBuf + Sync,
+    {
+        let out_dir = out_dir.as_ref();
+        let root = normalize_path(self.root);
+        let pages: Mutex<Vec<Option<BuildEntry>>> = Mutex::new((0..paths.len()).map(|_| None).collect());
+        let assets = AssetWrites::default();
+
+        self.batch_compile_stream(paths, |index, path, result| {
+            let source = normalize_path(path);
+            let mapped = path_mapper(source.strip_prefix(&root).unwrap_or(&source));
+            let output = out_dir.join(&mapped);
+            let written = match join_in_root(out_dir, &mapped) {
+                Ok(_) => result.and_then(|r| self.write_page(&r, &source, &output, out_dir, &assets)),
+                Err(err) => Err(err.into()),
+            };
+            let status = match written {
+                Ok(true) => BuildStatus::Written,
+                Ok(false) => BuildStatus::Unchanged,
+                Err(err) => BuildStatus::Failed(err),
+            };
+            pages.lock()[index] = Some(BuildEntry { source, output, status });
+        })?;
+
+        let mut assets = assets.state.into_inner().entries;
+        assets.sort_by(|a, b| a.output.cmp(&b.output));
+        Ok(BuildManifest {
+            pages: pages.into_inner().into_iter().flatten().collect(),
+            assets,
+        })
+    }
+
+    /// Write a page and its frame files. Returns `true` if the page changed.
+    #[cfg_attr(not(feature = "svg"), allow(unused_variables))]
+    fn write_page(
+        &self,
+        result: &CompileResult,
+        source: &Path,
+        output: &Path,
+        out_dir: &Path,
+        assets: &AssetWrites,
+    ) -> Result<bool, CompileError> {
+        #[cfg(feature = "svg")]
+        if let Some((mode, assets_dir)) = &self.frame_output {
+            let html = result.html_with(mode.clone())?;
+            let paths: Vec<_> = html.assets.iter().map(|asset| out_dir.join(assets_dir).join(&asset.name)).collect();
+
+            let (claimed, pending) = assets.claim(&paths);
+            let mut entries = Vec::with_capacity(claimed.len());
+            let mut failed = None;
+            for index in claimed {
+                let path = &paths[index];
+                let status = match write_if_changed(path, &html.assets[index].data) {
+                    Ok(true) => BuildStatus::Written,
+                    Ok(false) => BuildStatus::Unchanged,
+                    Err(err) => {
+                        failed.get_or_insert_with(|| format!("failed to write {}: {err}", path.display()));
+                        BuildStatus::Failed(err.into())
+                    }
+                };
+                entries.push(BuildEntry { source: source.to_path_buf(), output: path.clone(), status });
+            }
+            assets.finish(entries, &pending);
+
+            if let Some(message) = failed {
+                return Err(std::io::Error::other(message).into());
+            }
+            return Ok(write_if_changed(output, &html.html)?);
+        }
+
+        Ok(write_if_changed(output, &result.html()?)?)
+    }
+
+    /// Compile multiple files in parallel and aggregate their statistics.
+    ///
+    /// Like `batch_compile`, but also returns a [`BatchReport`] with summed
+    /// phase timings, file access counters and a slowest-files ranking.
+    /// Export time is not included, since serialization happens later via `html()`.
+    pub fn batch_compile_with_report<P: AsRef<Path> + Sync>(
+        &self,
+        paths: &[P],
+    ) -> Result<(Vec<Result<CompileResult, CompileError>>, BatchReport), CompileError> {
+        let start = Instant::now();
+        let results = self.batch_compile(paths)?;
+        let wall = start.elapsed();
+
+        let stats = paths
+            .iter()
+            .zip(&results)
+            .filter_map(|(path, r)| Some((path.as_ref(), r.as_ref().ok()?.stats())));
+        let failed = results.iter().filter(|r| r.is_err()).count();
+        let report = BatchReport::from_stats(stats, failed, wall);
+
+        Ok((results, report))
     }
 
     /// Compile multiple files in parallel with per-file context.
@@ -280,29 +552,72 @@ impl<'a> Batcher<'a> {
         P: AsRef<Path> + Sync,
         F: Fn(&Path) -> serde_json::Value + Sync,
     {
-        use rayon::prelude::*;
-
         if paths.is_empty() {
             return Ok(vec![]);
         }
 
         let snapshot = self.get_or_build_snapshot(paths)?;
 
+        let run = self.run(Task::Compile);
+
         // Compile in parallel with per-file context
-        let results: Vec<_> = paths
-            .par_iter()
-            .map(|path| {
-                let path = path.as_ref();
+        self.parallel.map_paths(paths, |index, path| {
+            run.file(index, path, || {
                 let context_json = context_fn(path);
                 let world = self.build_world_with_context(path, &snapshot, &context_json);
-                compile_with_world(&world)
+                self.finish(compile_with_world(&world))
             })
-            .collect();
+        })
+    }
 
-        Ok(results)
+    /// Compile multiple files in parallel to paged documents (PDF export).
+    ///
+    /// Shares the snapshot, fonts and inputs with `batch_compile`, but uses
+    /// the paged library (HTML feature disabled).
+    ///
+    /// Returns results in the same order as input paths.
+    #[cfg(feature = "pdf")]
+    pub fn batch_compile_paged<P: AsRef<Path> + Sync>(
+        &self,
+        paths: &[P],
+    ) -> Result<Vec<Result<PagedResult, CompileError>>, CompileError> {
+        if paths.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let snapshot = self.get_or_build_snapshot(paths)?;
+
+        let run = self.run(Task::Compile);
+
+        self.parallel.map_paths(paths, |index, path| {
+            run.file(index, path, || {
+                compile_paged_with_world(&self.world_builder(path, &snapshot).paged().build())
+            })
+        })
     }
 
     fn build_world(&self, path: &Path, snapshot: &Arc<FileSnapshot>) -> TypstWorld {
+        self.world_builder(path, snapshot).build()
+    }
+
+    fn run(&self, task: Task) -> BatchRun<'_> {
+        BatchRun {
+            observer: &self.observer,
+            budget: ErrorBudget::new(self.policy),
+            task,
+        }
+    }
+
+    /// Attach batch-wide state to a compile result.
+    fn finish(&self, result: Result<CompileResult, CompileError>) -> Result<CompileResult, CompileError> {
+        #[cfg(feature = "svg")]
+        if let Some(cache) = &self.frame_cache {
+            return result.map(|r| r.with_frame_cache(cache.clone()));
+        }
+        result
+    }
+
+    fn world_builder(&self, path: &Path, snapshot: &Arc<FileSnapshot>) -> WorldBuilder {
         let mut builder = TypstWorld::builder(path, self.root)
             .with_snapshot(snapshot.clone())
             .with_fonts();
@@ -317,7 +632,15 @@ impl<'a> Batcher<'a> {
             builder = builder.with_prelude(&prelude);
         }
 
-        builder.build()
+        self.timestamp.apply(self.interrupt.apply(builder))
+    }
+
+    fn snapshot_config(&self) -> Result<SnapshotConfig, CompileError> {
+        Ok(SnapshotConfig {
+            prelude: self.build_prelude_opt(),
+            postlude: self.build_postlude_opt(),
+            pool: self.parallel.pool()?,
+        })
     }
 
     fn build_prelude_opt(&self) -> Option<String> {
@@ -345,11 +668,9 @@ impl<'a> Batcher<'a> {
             None => {
                 let path_bufs: Vec<PathBuf> =
                     paths.iter().map(|p| p.as_ref().to_path_buf()).collect();
-                let config = SnapshotConfig {
-                    prelude: self.build_prelude_opt(),
-                    postlude: self.build_postlude_opt(),
-                };
-                Ok(Arc::new(FileSnapshot::build_with_config(&path_bufs, self.root, &config, |_| {})?))
+                let config = self.snapshot_config()?;
+                let on_load = |path: &Path| self.observer.emit(BatchEvent::SnapshotLoaded { path });
+                Ok(Arc::new(FileSnapshot::build_with_config(&path_bufs, self.root, &config, on_load)?))
             }
         }
     }
@@ -382,7 +703,7 @@ impl<'a> Batcher<'a> {
             builder = builder.with_prelude(&prelude);
         }
 
-        builder.build()
+        self.timestamp.apply(self.interrupt.apply(builder)).build()
     }
 }
 
@@ -397,6 +718,10 @@ pub struct BatchScanner<'a> {
     inputs: Option<Dict>,
     snapshot: Option<Arc<FileSnapshot>>,
     prelude: Option<String>,
+    interrupt: InterruptSettings,
+    timestamp: TimestampSettings,
+    parallel: ParallelSettings,
+    observer: Observer,
 }
 
 impl<'a> WithInputs for BatchScanner<'a> {
@@ -405,6 +730,24 @@ impl<'a> WithInputs for BatchScanner<'a> {
     }
 }
 
+impl<'a> WithInterrupt for BatchScanner<'a> {
+    fn interrupt_mut(&mut self) -> &mut InterruptSettings {
+        &mut self.interrupt
+    }
+}
+
+impl<'a> WithTimestamp for BatchScanner<'a> {
+    fn timestamp_mut(&mut self) -> &mut TimestampSettings {
+        &mut self.timestamp
+    }
+}
+
+impl<'a> WithParallelism for BatchScanner<'a> {
+    fn parallel_mut(&mut self) -> &mut ParallelSettings {
+        &mut self.parallel
+    }
+}
+
 impl<'a> BatchScanner<'a> {
     /// Create a new batch scanner with the given root directory.
     pub fn new(root: &'a Path) -> Self {
@@ -413,6 +756,10 @@ impl<'a> BatchScanner<'a> {
             inputs: None,
             snapshot: None,
             prelude: None,
+            interrupt: InterruptSettings::default(),
+            timestamp: TimestampSettings::default(),
+            parallel: ParallelSettings::default(),
+            observer: Observer::default(),
         }
     }
 
@@ -422,6 +769,14 @@ impl<'a> BatchScanner<'a> {
         self
     }
 
+    /// Report progress events from snapshot loading and scans.
+    ///
+    /// See [`Batcher::with_observer`].
+    pub fn with_observer(mut self, observer: impl Fn(&BatchEvent<'_>) + Send + Sync + 'static) -> Self {
+        self.observer = Observer::new(observer);
+        self
+    }
+
     /// Pre-build a snapshot from files for efficient batch scanning.
     pub fn with_snapshot_from<P: AsRef<Path>>(mut self, paths: &[P]) -> Result<Self, CompileError> {
         if paths.is_empty() {
@@ -429,11 +784,9 @@ impl<'a> BatchScanner<'a> {
         }
 
         let path_bufs: Vec<PathBuf> = paths.iter().map(|p| p.as_ref().to_path_buf()).collect();
-        let config = SnapshotConfig {
-            prelude: self.prelude.clone(),
-            postlude: None,
-        };
-        let snapshot = Arc::new(FileSnapshot::build_with_config(&path_bufs, self.root, &config, |_| {})?);
+        let config = self.snapshot_config()?;
+        let on_load = |path: &Path| self.observer.emit(BatchEvent::SnapshotLoaded { path });
+        let snapshot = Arc::new(FileSnapshot::build_with_config(&path_bufs, self.root, &config, on_load)?);
         self.snapshot = Some(snapshot);
 
         Ok(self)
@@ -445,8 +798,6 @@ impl<'a> BatchScanner<'a> {
         &self,
         paths: &[P],
     ) -> Result<Vec<Result<ScanResult, CompileError>>, CompileError> {
-        use rayon::prelude::*;
-
         if paths.is_empty() {
             return Ok(vec![]);
         }
@@ -457,25 +808,30 @@ impl<'a> BatchScanner<'a> {
             None => {
                 let path_bufs: Vec<PathBuf> =
                     paths.iter().map(|p| p.as_ref().to_path_buf()).collect();
-                let config = SnapshotConfig {
-                    prelude: self.prelude.clone(),
-                    postlude: None,
-                };
-                Arc::new(FileSnapshot::build_with_config(&path_bufs, self.root, &config, |_| {})?)
+                let config = self.snapshot_config()?;
+                let on_load = |path: &Path| self.observer.emit(BatchEvent::SnapshotLoaded { path });
+                Arc::new(FileSnapshot::build_with_config(&path_bufs, self.root, &config, on_load)?)
             }
         };
 
+        let run = BatchRun {
+            observer: &self.observer,
+            budget: ErrorBudget::new(FailurePolicy::Continue),
+            task: Task::Scan,
+        };
+
         // Scan in parallel with lightweight world (no fonts)
-        let results: Vec<_> = paths
-            .par_iter()
-            .map(|path| {
-                let path = path.as_ref();
-                let world = self.build_world(path, &snapshot);
-                scan_impl(&world)
-            })
-            .collect();
+        self.parallel.map_paths(paths, |index, path| {
+            run.file(index, path, || scan_impl(self.build_world(path, &snapshot)))
+        })
+    }
 
-        Ok(results)
+    fn snapshot_config(&self) -> Result<SnapshotConfig, CompileError> {
+        Ok(SnapshotConfig {
+            prelude: self.prelude.clone(),
+            postlude: None,
+            pool: self.parallel.pool()?,
+        })
     }
 
     fn build_world(&self, path: &Path, snapshot: &Arc<FileSnapshot>) -> TypstWorld {
@@ -492,6 +848,101 @@ impl<'a> BatchScanner<'a> {
             builder = builder.with_prelude(prelude);
         }
 
-        builder.build()
+        self.timestamp.apply(self.interrupt.apply(builder)).build()
+    }
+}
+
+/// Frame files written by one `build_to` call, shared by its pages.
+///
+/// Each file is written by the first page that references it, outside the
+/// lock; other pages wait for it before writing themselves.
+#[derive(Default)]
+#[cfg_attr(not(feature = "svg"), allow(dead_code))]
+struct AssetWrites {
+    state: Mutex<AssetState>,
+    written: Condvar,
+}
+
+#[derive(Default)]
+#[cfg_attr(not(feature = "svg"), allow(dead_code))]
+struct AssetState {
+    /// Claimed files, and whether their write has finished.
+    done: FxHashMap<PathBuf, bool>,
+    entries: Vec<BuildEntry>,
+}
+
+#[cfg(feature = "svg")]
+impl AssetWrites {
+    /// Claim the unclaimed `paths`. Returns the indices of claimed paths
+    /// and the paths other pages are still writing.
+    fn claim(&self, paths: &[PathBuf]) -> (Vec<usize>, Vec<PathBuf>) {
+        let mut state = self.state.lock();
+        let mut claimed = Vec::new();
+        let mut pending = Vec::new();
+        for (index, path) in paths.iter().enumerate() {
+            match state.done.get(path) {
+                None => {
+                    state.done.insert(path.clone(), false);
+                    claimed.push(index);
+                }
+                Some(false) => pending.push(path.clone()),
+                Some(true) => {}
+            }
+        }
+        (claimed, pending)
+    }
+
+    /// Record finished writes, then wait until `pending` are written too.
+    fn finish(&self, entries: Vec<BuildEntry>, pending: &[PathBuf]) {
+        let mut state = self.state.lock();
+        for entry in &entries {
+            state.done.insert(entry.output.clone(), true);
+        }
+        state.entries.extend(entries);
+        self.written.notify_all();
+        while pending.iter().any(|path| state.done.get(path) == Some(&false)) {
+            self.written.wait(&mut state);
+        }
+    }
+}
+
+/// State shared by the files of one batch call.
+struct BatchRun<'a> {
+    observer: &'a Observer,
+    budget: ErrorBudget,
+    task: Task,
+}
+
+impl BatchRun<'_> {
+    /// Process one file under the error budget, reporting events.
+    fn file<T: Outcome>(
+        &self,
+        index: usize,
+        path: &Path,
+        work: impl FnOnce() -> Result<T, CompileError>,
+    ) -> Result<T, CompileError> {
+        self.file_then(index, path, work, |result| result)
+    }
+
+    /// Like [`file`](Self::file), but hands the result to `then` before the
+    /// memory reservation is released.
+    fn file_then<T: Outcome, R>(
+        &self,
+        index: usize,
+        path: &Path,
+        work: impl FnOnce() -> Result<T, CompileError>,
+        then: impl FnOnce(Result<T, CompileError>) -> R,
+    ) -> R {
+        if self.budget.is_exhausted() {
+            self.observer.emit(BatchEvent::Skipped { index, path });
+            return then(Err(CompileError::Skipped));
+        }
+
+        self.observer.emit(self.task.started(index, path));
+        let start = Instant::now();
+        let result = work();
+        self.observer.emit(self.task.finished(index, path, start.elapsed(), &result));
+        self.budget.record(&result);
+        then(result)
     }
 }
@@ -0,0 +1,160 @@
+//! Build a batch into an output directory.
+//!
+//! See [`Batcher::build_to`](super::batch::Batcher::build_to).
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::diagnostic::CompileError;
+
+/// What happened to one output file.
+#[derive(Debug)]
+pub enum BuildStatus {
+    /// The file was created or its content changed.
+    Written,
+    /// The existing file already had this content and was not touched.
+    Unchanged,
+    /// Compilation or writing failed (or the file was skipped by the
+    /// [`FailurePolicy`](super::FailurePolicy)); any existing file is kept.
+    Failed(CompileError),
+}
+
+/// An output file of a build.
+#[derive(Debug)]
+pub struct BuildEntry {
+    /// The source file that produced the output.
+    pub source: PathBuf,
+    /// The output file path.
+    pub output: PathBuf,
+    /// Whether the file was written.
+    pub status: BuildStatus,
+}
+
+/// Outputs of [`Batcher::build_to`](super::batch::Batcher::build_to).
+#[derive(Debug, Default)]
+pub struct BuildManifest {
+    pub(crate) pages: Vec<BuildEntry>,
+    pub(crate) assets: Vec<BuildEntry>,
+}
+
+impl BuildManifest {
+    /// Get the HTML pages, in input order.
+    pub fn pages(&self) -> &[BuildEntry] {
+        &self.pages
+    }
+
+    /// Get the frame files, each listed once with the first page using it.
+    pub fn assets(&self) -> &[BuildEntry] {
+        &self.assets
+    }
+
+    /// Iterate over pages and assets that were written.
+    pub fn written(&self) -> impl Iterator<Item = &BuildEntry> {
+        self.entries().filter(|e| matches!(e.status, BuildStatus::Written))
+    }
+
+    /// Iterate over pages and assets that were left untouched.
+    pub fn unchanged(&self) -> impl Iterator<Item = &BuildEntry> {
+        self.entries().filter(|e| matches!(e.status, BuildStatus::Unchanged))
+    }
+
+    /// Iterate over failed pages and assets.
+    pub fn failed(&self) -> impl Iterator<Item = (&BuildEntry, &CompileError)> {
+        self.entries().filter_map(|e| match &e.status {
+            BuildStatus::Failed(err) => Some((e, err)),
+            _ => None,
+        })
+    }
+
+    /// Check if every page and asset was built.
+    pub fn is_success(&self) -> bool {
+        self.failed().next().is_none()
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &BuildEntry> {
+        self.pages.iter().chain(&self.assets)
+    }
+}
+
+/// Map a source path to a "pretty URL" output path.
+///
+/// `foo.typ` → `foo/index.html`, `blog/post.typ` → `blog/post/index.html`,
+/// and `index.typ` stays `index.html`, so pages are served at `/foo/`.
+///
+/// # Example
+///
+/// ```ignore
+/// batcher.build_to(&files, "public", |path| {
+///     pretty_html_path(path.strip_prefix("content").unwrap_or(path))
+/// })?;
+/// ```
+pub fn pretty_html_path(path: &Path) -> PathBuf {
+    if path.file_stem().is_some_and(|stem| stem == "index") {
+        path.with_extension("html")
+    } else {
+        path.with_extension("").join("index.html")
+    }
+}
+
+/// Write `data` to `path` unless the file already has exactly this content.
+///
+/// Writes go to a temporary file in the same directory, which is then
+/// renamed over `path`, so readers never see a partially written file.
+/// Returns `true` if the file was written.
+pub(crate) fn write_if_changed(path: &Path, data: &[u8]) -> io::Result<bool> {
+    if let Ok(meta) = fs::metadata(path)
+        && meta.len() == data.len() as u64
+        && fs::read(path)? == data
+    {
+        return Ok(false);
+    }
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(dir)?;
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let tmp = dir.join(format!(".{name}.{}.{n}.tmp", std::process::id()));
+
+    let result = fs::File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result.map(|()| true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pretty_html_path() {
+        assert_eq!(pretty_html_path(Path::new("foo.typ")), Path::new("foo/index.html"));
+        assert_eq!(pretty_html_path(Path::new("blog/post.typ")), Path::new("blog/post/index.html"));
+        assert_eq!(pretty_html_path(Path::new("index.typ")), Path::new("index.html"));
+        assert_eq!(pretty_html_path(Path::new("blog/index.typ")), Path::new("blog/index.html"));
+    }
+
+    #[test]
+    fn test_write_if_changed() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a/b/index.html");
+
+        assert!(write_if_changed(&path, b"one").unwrap());
+        assert!(!write_if_changed(&path, b"one").unwrap());
+        assert!(write_if_changed(&path, b"two").unwrap());
+        assert_eq!(fs::read(&path).unwrap(), b"two");
+
+        // No temporary files left behind
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+}
@@ -1,10 +1,12 @@
 //! Common utilities shared between compile and scan.
 
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use rustc_hash::FxHashSet;
 
 use crate::resource::file::{get_accessed_files, is_virtual_path, reset_access_flags, PackageId};
+use crate::world::TypstWorld;
 
 /// Reset file access tracking before compilation/scanning.
 #[inline]
@@ -45,3 +47,38 @@ pub fn collect_accessed_packages() -> Vec<PackageId> {
         .filter(|pkg| seen.insert(pkg.clone()))
         .collect()
 }
+
+/// Run Eval ahead of `typst::compile` and return how long it took.
+///
+/// Eval is memoized by comemo, so the following `typst::compile` reuses the
+/// result and its own time is effectively Layout only. Errors are ignored
+/// here; the compile call reports them.
+#[cfg(feature = "scan")]
+pub fn timed_eval(world: &TypstWorld) -> Duration {
+    use std::time::Instant;
+    use typst::comemo::Track;
+    use typst::engine::{Route, Sink, Traced};
+    use typst::World;
+
+    let start = Instant::now();
+    if let Ok(source) = world.source(world.main()) {
+        let traced = Traced::default();
+        let mut sink = Sink::new();
+        let world_ref: &dyn World = world;
+        let _ = typst_eval::eval(
+            &typst::ROUTINES,
+            world_ref.track(),
+            traced.track(),
+            sink.track_mut(),
+            Route::default().track(),
+            &source,
+        );
+    }
+    start.elapsed()
+}
+
+/// Without the `scan` feature, Eval is timed as part of Layout.
+#[cfg(not(feature = "scan"))]
+pub fn timed_eval(_world: &TypstWorld) -> Duration {
+    Duration::ZERO
+}
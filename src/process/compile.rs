@@ -17,6 +17,11 @@
 //!     .with_path(Path::new("doc.typ"))
 //!     .compile()?;
 //!
+//! // In-memory main file (no temp file needed)
+//! let result = Compiler::new(Path::new("."))
+//!     .with_source("input.typ", "#import \"lib.typ\": title\n= #title")
+//!     .compile()?;
+//!
 //! // Custom World (advanced)
 //! let result = Compiler::new(Path::new("."))
 //!     .with_path(Path::new("doc.typ"))
@@ -37,16 +42,27 @@
 //! ```
 
 use std::path::{Path, PathBuf};
+use std::time::Instant;
+#[cfg(feature = "svg")]
+use std::sync::Arc;
 
 use typst::foundations::Dict;
 
 use crate::diagnostic::{filter_html_warnings, has_errors, CompileError, Diagnostics};
-use crate::html::HtmlDocument;
-use crate::world::TypstWorld;
+use crate::html::{HtmlDocument, HtmlSerializer};
+use crate::resource::file::PackageId;
+use crate::world::{join_in_root, TypstWorld, WorldBuilder};
+#[cfg(feature = "svg")]
+use crate::html::{FrameCache, FrameMode, HtmlOutput};
 
+use super::common::timed_eval;
 use super::inputs::WithInputs;
+use super::interrupt::{InterruptSettings, WithInterrupt};
 use super::session::{AccessedDeps, CompileSession};
-use crate::resource::file::PackageId;
+use super::stats::{CompileStats, ExportTimer};
+use super::timestamp::{TimestampSettings, WithTimestamp};
+#[cfg(feature = "pdf")]
+use super::paged::{compile_paged_with_world, PagedResult};
 
 /// Type alias for custom World builder function.
 type WorldBuilderFn<'a> = Box<dyn FnOnce(MainPath<'_>, RootPath<'_>) -> TypstWorld + 'a>;
@@ -120,6 +136,8 @@ pub struct Compiler<'a> {
     inputs: Option<Dict>,
     preludes: Vec<String>,
     postludes: Vec<String>,
+    interrupt: InterruptSettings,
+    timestamp: TimestampSettings,
 }
 
 impl<'a> WithInputs for Compiler<'a> {
@@ -128,6 +146,18 @@ impl<'a> WithInputs for Compiler<'a> {
     }
 }
 
+impl<'a> WithInterrupt for Compiler<'a> {
+    fn interrupt_mut(&mut self) -> &mut InterruptSettings {
+        &mut self.interrupt
+    }
+}
+
+impl<'a> WithTimestamp for Compiler<'a> {
+    fn timestamp_mut(&mut self) -> &mut TimestampSettings {
+        &mut self.timestamp
+    }
+}
+
 impl<'a> Compiler<'a> {
     /// Create a new compiler with the given root directory.
     pub fn new(root: &'a Path) -> Self {
@@ -136,6 +166,8 @@ impl<'a> Compiler<'a> {
             inputs: None,
             preludes: Vec::new(),
             postludes: Vec::new(),
+            interrupt: InterruptSettings::default(),
+            timestamp: TimestampSettings::default(),
         }
     }
 
@@ -159,14 +191,40 @@ impl<'a> Compiler<'a> {
             inputs: self.inputs,
             preludes: self.preludes,
             postludes: self.postludes,
+            interrupt: self.interrupt,
+            timestamp: self.timestamp,
+            source: None,
+            invalid_source: None,
             world_builder: None,
         }
     }
 
+    /// Compile in-memory text as the main file, returning a [`SingleCompiler`].
+    ///
+    /// `name` is a path relative to the root (e.g., `"input.typ"`). No file is
+    /// written: the text is served as a virtual main file at that location, so
+    /// relative imports resolve against it and diagnostics report `name`.
+    /// Real files under the root can still be imported.
+    ///
+    /// Absolute names and names with `..` components fail to compile with
+    /// [`CompileError::Io`], so the text cannot pose as a file outside the root.
+    pub fn with_source(self, name: impl AsRef<Path>, text: impl Into<String>) -> SingleCompiler<'a> {
+        let name = name.as_ref();
+        let (path, invalid) = match join_in_root(self.root, name) {
+            Ok(path) => (path, None),
+            Err(err) => (name.to_path_buf(), Some(err)),
+        };
+        let mut compiler = self.with_path(path);
+        compiler.source = Some(text.into());
+        compiler.invalid_source = invalid;
+        compiler
+    }
+
     /// Convert to batch compilation mode.
     ///
     /// Returns a [`Batcher`](super::batch::Batcher) for parallel compilation with snapshot optimization.
-    /// Any `with_inputs()` settings are inherited.
+    /// Any `with_inputs()`, `with_cancellation()`, `with_timeout()`, `with_timestamp()`
+    /// and `reproducible()` settings are inherited.
     ///
     /// **Note**: Batch mode uses lock-free snapshot caching internally.
     /// Custom `with_world()` settings from single-file mode do not apply.
@@ -178,6 +236,8 @@ impl<'a> Compiler<'a> {
         }
         batcher.preludes = self.preludes;
         batcher.postludes = self.postludes;
+        batcher.interrupt = self.interrupt;
+        batcher.timestamp = self.timestamp;
         batcher
     }
 }
@@ -210,6 +270,11 @@ pub struct SingleCompiler<'a> {
     inputs: Option<Dict>,
     preludes: Vec<String>,
     postludes: Vec<String>,
+    interrupt: InterruptSettings,
+    timestamp: TimestampSettings,
+    source: Option<String>,
+    /// Why the `with_source()` name was rejected.
+    invalid_source: Option<std::io::Error>,
     world_builder: Option<WorldBuilderFn<'a>>,
 }
 
@@ -219,6 +284,18 @@ impl<'a> WithInputs for SingleCompiler<'a> {
     }
 }
 
+impl<'a> WithInterrupt for SingleCompiler<'a> {
+    fn interrupt_mut(&mut self) -> &mut InterruptSettings {
+        &mut self.interrupt
+    }
+}
+
+impl<'a> WithTimestamp for SingleCompiler<'a> {
+    fn timestamp_mut(&mut self) -> &mut TimestampSettings {
+        &mut self.timestamp
+    }
+}
+
 impl<'a> SingleCompiler<'a> {
     /// Add prelude code to inject at the beginning of the main file.
     pub fn with_prelude(mut self, prelude: impl Into<String>) -> Self {
@@ -237,6 +314,11 @@ impl<'a> SingleCompiler<'a> {
     /// The closure receives type-safe path wrappers that can only be obtained
     /// through this API, ensuring the World's paths match the compiler's paths.
     ///
+    /// `with_cancellation()`, `with_timeout()`, `with_timestamp()`, `reproducible()`
+    /// and `with_source()` text do not
+    /// apply to a custom World; configure them on the [`WorldBuilder`] instead
+    /// (see [`WorldBuilder::with_main_source`]).
+    ///
     /// # Example
     ///
     /// ```ignore
@@ -259,7 +341,10 @@ impl<'a> SingleCompiler<'a> {
     }
 
     /// Compile the file.
-    pub fn compile(self) -> Result<CompileResult, CompileError> {
+    pub fn compile(mut self) -> Result<CompileResult, CompileError> {
+        if let Some(err) = self.invalid_source.take() {
+            return Err(err.into());
+        }
         let world = match self.world_builder {
             Some(builder) => builder(MainPath(&self.path), RootPath(self.root)),
             None => self.default_world(),
@@ -267,7 +352,27 @@ impl<'a> SingleCompiler<'a> {
         compile_with_world(&world)
     }
 
+    /// Compile the file to a paged document for PDF export.
+    ///
+    /// Uses the paged library (HTML feature disabled). A custom World from
+    /// `with_world()` should be built with [`WorldBuilder::paged`].
+    #[cfg(feature = "pdf")]
+    pub fn compile_paged(mut self) -> Result<PagedResult, CompileError> {
+        if let Some(err) = self.invalid_source.take() {
+            return Err(err.into());
+        }
+        let world = match self.world_builder {
+            Some(builder) => builder(MainPath(&self.path), RootPath(self.root)),
+            None => self.default_builder().paged().build(),
+        };
+        compile_paged_with_world(&world)
+    }
+
     fn default_world(&self) -> TypstWorld {
+        self.default_builder().build()
+    }
+
+    fn default_builder(&self) -> WorldBuilder {
         let mut builder = TypstWorld::builder(&self.path, self.root)
             .with_shared_cache()
             .with_fonts();
@@ -275,6 +380,12 @@ impl<'a> SingleCompiler<'a> {
         if let Some(inputs) = &self.inputs {
             builder = builder.with_inputs_dict(inputs.clone());
         }
+        builder = self.interrupt.apply(builder);
+        builder = self.timestamp.apply(builder);
+
+        if let Some(source) = &self.source {
+            builder = builder.with_main_source(source.clone());
+        }
 
         // Build combined prelude: styles + scripts + user preludes
         let combined_prelude = self.build_prelude();
@@ -288,7 +399,7 @@ impl<'a> SingleCompiler<'a> {
             builder = builder.with_postlude(combined_postlude);
         }
 
-        builder.build()
+        builder
     }
 
     fn build_prelude(&self) -> String {
@@ -304,6 +415,10 @@ pub struct CompileResult {
     document: HtmlDocument,
     accessed: AccessedDeps,
     diagnostics: Diagnostics,
+    stats: CompileStats,
+    export: ExportTimer,
+    #[cfg(feature = "svg")]
+    frame_cache: Option<Arc<FrameCache>>,
 }
 
 impl CompileResult {
@@ -313,10 +428,81 @@ impl CompileResult {
     }
 
     /// Convert the document to HTML bytes.
+    ///
+    /// The serialization time is added to [`stats().export`](CompileStats::export).
     pub fn html(&self) -> Result<Vec<u8>, CompileError> {
-        typst_html::html(self.document.as_inner())
+        let start = Instant::now();
+        let html = typst_html::html(self.document.as_inner())
             .map(|s| s.into_bytes())
-            .map_err(|e| CompileError::html_export(format!("{e:?}")))
+            .map_err(|e| CompileError::html_export(format!("{e:?}")));
+        self.export.record(start.elapsed());
+        html
+    }
+
+    /// Convert the document to a finished HTML page, writing frames per `mode`.
+    ///
+    /// External frame files are returned in [`HtmlOutput::assets`]. Frames are
+    /// rendered through the attached [`FrameCache`], if any. The time is added
+    /// to [`stats().export`](CompileStats::export).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let output = result.html_with(FrameMode::external("/frames/"))?;
+    /// output.write_assets(out_dir.join("frames"))?;
+    /// std::fs::write(out_dir.join("index.html"), &output.html)?;
+    /// ```
+    #[cfg(feature = "svg")]
+    pub fn html_with(&self, mode: FrameMode) -> Result<HtmlOutput, CompileError> {
+        let start = Instant::now();
+        let cache = self.frame_cache.as_deref();
+        let output = crate::html::html_with_frames(&self.document, &mode, cache);
+        self.export.record(start.elapsed());
+        output
+    }
+
+    /// Attach a frame cache used by [`html_with`](Self::html_with).
+    ///
+    /// [`Batcher::with_frame_cache`](super::batch::Batcher::with_frame_cache)
+    /// attaches its cache to every result automatically.
+    #[cfg(feature = "svg")]
+    pub fn with_frame_cache(mut self, cache: Arc<FrameCache>) -> Self {
+        self.frame_cache = Some(cache);
+        self
+    }
+
+    /// Get the attached frame cache.
+    #[cfg(feature = "svg")]
+    pub fn frame_cache(&self) -> Option<&Arc<FrameCache>> {
+        self.frame_cache.as_ref()
+    }
+
+    /// Convert the document to HTML bytes with a configured [`HtmlSerializer`].
+    ///
+    /// Use this for minified or re-indented output, sorted attributes, or
+    /// per-node rewriting. The time is added to [`stats().export`](CompileStats::export).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let html = result.html_with_serializer(&mut HtmlSerializer::new().minify())?;
+    /// ```
+    pub fn html_with_serializer(
+        &self,
+        serializer: &mut HtmlSerializer<'_>,
+    ) -> Result<Vec<u8>, CompileError> {
+        let start = Instant::now();
+        let html = serializer.serialize(&self.document).map(String::into_bytes);
+        self.export.record(start.elapsed());
+        html
+    }
+
+    /// Get per-phase timing and file access statistics.
+    pub fn stats(&self) -> CompileStats {
+        CompileStats {
+            export: self.export.get(),
+            ..self.stats
+        }
     }
 
     /// Get files and packages accessed during compilation.
@@ -352,13 +538,30 @@ impl CompileResult {
     }
 }
 
+impl AsRef<AccessedDeps> for CompileResult {
+    fn as_ref(&self) -> &AccessedDeps {
+        &self.accessed
+    }
+}
+
 
 
 pub(crate) fn compile_with_world(world: &TypstWorld) -> Result<CompileResult, CompileError> {
+    world.check_interrupt()?;
+    let start = Instant::now();
     let session = CompileSession::start();
     let line_offset = world.prelude_line_count();
 
+    let eval = timed_eval(world);
+    world.check_interrupt()?;
+
+    let layout_start = Instant::now();
     let result = typst::compile(world);
+    let layout = layout_start.elapsed();
+    if result.output.is_err() || has_errors(&result.warnings) {
+        // Errors may stem from file accesses refused by the interrupt
+        world.check_interrupt()?;
+    }
 
     if has_errors(&result.warnings) {
         return Err(CompileError::compilation_with_offset(world, result.warnings.to_vec(), line_offset));
@@ -370,16 +573,28 @@ pub(crate) fn compile_with_world(world: &TypstWorld) -> Result<CompileResult, Co
         CompileError::compilation_with_offset(world, filtered, line_offset)
     })?;
 
-    let document = HtmlDocument::new(document);
+    let document = HtmlDocument::new(document).with_library(world.library_strategy().clone());
 
     let accessed = session.finish(world.root());
     let filtered_warnings = filter_html_warnings(&result.warnings);
     let diagnostics = Diagnostics::resolve_with_offset(world, &filtered_warnings, line_offset);
 
+    let stats = CompileStats {
+        eval,
+        layout,
+        total: start.elapsed(),
+        files: world.file_stats(),
+        ..Default::default()
+    };
+
     Ok(CompileResult {
         document,
         accessed,
         diagnostics,
+        stats,
+        export: ExportTimer::default(),
+        #[cfg(feature = "svg")]
+        frame_cache: None,
     })
 }
 
@@ -472,6 +687,211 @@ mod tests {
         assert_eq!(metas[1].get("id").and_then(|v| v.as_i64()), Some(2));
     }
 
+    #[test]
+    fn test_compile_with_source() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("lib.typ"), r#"#let title = "From Disk""#).unwrap();
+
+        let result = Compiler::new(dir.path())
+            .with_prelude("#let suffix = \"!\"")
+            .with_source("input.typ", "#import \"lib.typ\": title\n= #title#suffix")
+            .compile()
+            .unwrap();
+
+        let html = String::from_utf8(result.html().unwrap()).unwrap();
+        assert!(html.contains("From Disk!"));
+        assert!(!dir.path().join("input.typ").exists());
+    }
+
+    #[test]
+    fn test_compile_with_source_diagnostics() {
+        let dir = TempDir::new().unwrap();
+
+        let err = Compiler::new(dir.path())
+            .with_prelude("#let unused = 1")
+            .with_source("pages/input.typ", "= Title\n#undefined")
+            .compile()
+            .unwrap_err();
+
+        let diag = err.diagnostics().unwrap().errors().next().unwrap();
+        assert_eq!(diag.path.as_deref(), Some("pages/input.typ"));
+        assert_eq!(diag.line, Some(2));
+    }
+
+    #[test]
+    fn test_compile_with_source_outside_root() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("site");
+        fs::create_dir(&root).unwrap();
+
+        for name in ["../x.typ", "pages/../../x.typ"] {
+            let err = Compiler::new(&root).with_source(name, "= X").compile().unwrap_err();
+            assert!(matches!(err, CompileError::Io(_)), "{name}: {err}");
+        }
+        let absolute = dir.path().join("x.typ");
+        let err = Compiler::new(&root).with_source(&absolute, "= X").compile().unwrap_err();
+        assert!(matches!(err, CompileError::Io(_)));
+
+        assert!(Compiler::new(&root).with_source("./pages/x.typ", "= X").compile().is_ok());
+    }
+
+    #[test]
+    fn test_compile_reproducible() {
+        let dir = TempDir::new().unwrap();
+        let timestamp = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let source = "= Build\n#datetime.today().display()\n#lorem(20)";
+
+        // Different main files, so the two worlds share no memoized
+        // evaluation of the page (without evicting the global memo cache)
+        let compile = |name: &str| {
+            Compiler::new(dir.path())
+                .with_timestamp(timestamp)
+                .reproducible()
+                .with_source(name, source)
+                .compile()
+                .unwrap()
+                .html()
+                .unwrap()
+        };
+
+        let first = compile("a/page.typ");
+        assert!(String::from_utf8_lossy(&first).contains("2023-11-14"));
+        assert_eq!(typst::utils::hash128(&first), typst::utils::hash128(&compile("b/page.typ")));
+    }
+
+    #[test]
+    fn test_compile_stats() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("test.typ");
+        fs::write(dir.path().join("lib.typ"), "#let x = 1").unwrap();
+        fs::write(&file, "#import \"lib.typ\": x\n= Stats #x").unwrap();
+
+        // Default (shared cache) world
+        let result = Compiler::new(dir.path()).with_path(&file).compile().unwrap();
+
+        let stats = result.stats();
+        assert!(stats.total >= stats.eval + stats.layout);
+        assert_eq!(stats.export, std::time::Duration::ZERO);
+        // Other tests may start a new compilation generation mid-way,
+        // which makes the shared cache re-validate a file once more
+        assert!(stats.files.files_read >= 2);
+        assert!(stats.files.bytes_read > 0);
+        assert_eq!(stats.files.shared_lookups, stats.files.files_read + stats.files.cache_hits);
+
+        result.html().unwrap();
+        assert!(result.stats().export > std::time::Duration::ZERO);
+
+        let result = Compiler::new(dir.path())
+            .with_path(&file)
+            .with_world(|main, root| {
+                TypstWorld::builder(main.as_path(), root.as_path())
+                    .with_local_cache()
+                    .with_fonts()
+                    .build()
+            })
+            .compile()
+            .unwrap();
+        assert_eq!(result.stats().files.files_read, 2);
+    }
+
+    #[test]
+    #[cfg(feature = "batch")]
+    fn test_batch_compile_with_report() {
+        let dir = TempDir::new().unwrap();
+        let file1 = dir.path().join("test1.typ");
+        let file2 = dir.path().join("test2.typ");
+        fs::write(&file1, "= File One").unwrap();
+        fs::write(&file2, "#undefined").unwrap();
+
+        let (results, report) = Compiler::new(dir.path())
+            .into_batch()
+            .batch_compile_with_report(&[&file1, &file2])
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(report.succeeded(), 1);
+        assert_eq!(report.failed(), 1);
+        assert!(report.totals().files.snapshot_hits > 0);
+        assert_eq!(report.slowest(5).next().unwrap().0, file1.as_path());
+    }
+
+    #[test]
+    fn test_compile_cancelled() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("test.typ");
+        fs::write(&file, "= Hello").unwrap();
+
+        let token = crate::world::CancellationToken::new();
+        token.cancel();
+
+        let result = Compiler::new(dir.path())
+            .with_cancellation(token)
+            .with_path(&file)
+            .compile();
+        assert!(matches!(result, Err(CompileError::Cancelled)));
+    }
+
+    #[test]
+    fn test_compile_timed_out() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("test.typ");
+        fs::write(&file, "= Hello").unwrap();
+
+        let result = Compiler::new(dir.path())
+            .with_timeout(std::time::Duration::ZERO)
+            .with_path(&file)
+            .compile();
+        assert!(result.unwrap_err().is_interrupted());
+
+        let result = Compiler::new(dir.path())
+            .with_timeout(std::time::Duration::from_secs(60))
+            .with_path(&file)
+            .compile();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_compile_cpu_loop_interrupted() {
+        use std::time::Duration;
+
+        // Interrupts are checked before evaluation, so the loop never runs
+        const LOOP: &str = "#let n = 0\n#for i in range(20000) { n += i }\n= #n";
+        let dir = TempDir::new().unwrap();
+        let result = Compiler::new(dir.path())
+            .with_timeout(Duration::ZERO)
+            .with_source("loop.typ", LOOP)
+            .compile();
+        assert!(matches!(result, Err(CompileError::TimedOut { .. })));
+
+        let token = crate::world::CancellationToken::new();
+        token.cancel();
+        let result = Compiler::new(dir.path())
+            .with_cancellation(token)
+            .with_source("loop.typ", LOOP)
+            .compile();
+        assert!(matches!(result, Err(CompileError::Cancelled)));
+    }
+
+    #[test]
+    #[cfg(feature = "batch")]
+    fn test_batch_cancelled() {
+        let dir = TempDir::new().unwrap();
+        let file1 = dir.path().join("test1.typ");
+        let file2 = dir.path().join("test2.typ");
+        fs::write(&file1, "= File One").unwrap();
+        fs::write(&file2, "= File Two").unwrap();
+
+        let token = crate::world::CancellationToken::new();
+        let batcher = Compiler::new(dir.path())
+            .with_cancellation(token.clone())
+            .into_batch();
+        token.cancel();
+
+        let results = batcher.batch_compile(&[&file1, &file2]).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| matches!(r, Err(CompileError::Cancelled))));
+    }
+
     #[test]
     #[cfg(feature = "batch")]
     fn test_batch_compile() {
@@ -497,6 +917,245 @@ mod tests {
         assert!(String::from_utf8_lossy(&html2).contains("File Two"));
     }
 
+    #[test]
+    #[cfg(feature = "batch")]
+    fn test_batch_compile_stream() {
+        let dir = TempDir::new().unwrap();
+        let files: Vec<_> = (0..4)
+            .map(|i| {
+                let file = dir.path().join(format!("test{i}.typ"));
+                fs::write(&file, if i == 2 { "#undefined".to_string() } else { format!("= File {i}") }).unwrap();
+                file
+            })
+            .collect();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        Compiler::new(dir.path())
+            .into_batch()
+            .batch_compile_stream(&files, |index, path, result| {
+                assert_eq!(path, files[index]);
+                let html = result.and_then(|r| r.html()).map(|html| String::from_utf8(html).unwrap());
+                tx.send((index, html)).unwrap();
+            })
+            .unwrap();
+        drop(tx);
+
+        let mut received: Vec<_> = rx.into_iter().collect();
+        received.sort_by_key(|(index, _)| *index);
+        assert_eq!(received.len(), 4);
+        assert!(received[0].1.as_ref().unwrap().contains("File 0"));
+        assert!(received[2].1.is_err());
+        assert!(received[3].1.as_ref().unwrap().contains("File 3"));
+    }
+
+    #[test]
+    #[cfg(feature = "batch")]
+    fn test_batch_build_to() {
+        use crate::process::{pretty_html_path, BuildStatus};
+
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("src");
+        let out = dir.path().join("out");
+        fs::create_dir_all(src.join("blog")).unwrap();
+        let index = src.join("index.typ");
+        let post = src.join("blog/post.typ");
+        let broken = src.join("broken.typ");
+        fs::write(&index, "= Home").unwrap();
+        fs::write(&post, "= Post").unwrap();
+        fs::write(&broken, "#undefined").unwrap();
+        let files = [&index, &post, &broken];
+
+        let batcher = Compiler::new(&src).into_batch();
+        let manifest = batcher.build_to(&files, &out, pretty_html_path).unwrap();
+        assert_eq!(manifest.pages().len(), 3);
+        assert_eq!(manifest.pages()[1].output, out.join("blog/post/index.html"));
+        assert_eq!(manifest.written().count(), 2);
+        assert_eq!(manifest.failed().count(), 1);
+        assert!(!manifest.is_success());
+        assert!(fs::read_to_string(out.join("index.html")).unwrap().contains("Home"));
+        assert!(!out.join("broken/index.html").exists());
+
+        // Rebuild with one changed page
+        fs::write(&post, "= Edited").unwrap();
+        let batcher = Compiler::new(&src).into_batch();
+        let manifest = batcher.build_to(&files, &out, pretty_html_path).unwrap();
+        assert!(matches!(manifest.pages()[0].status, BuildStatus::Unchanged));
+        assert!(matches!(manifest.pages()[1].status, BuildStatus::Written));
+        assert!(matches!(manifest.pages()[2].status, BuildStatus::Failed(_)));
+        assert!(fs::read_to_string(out.join("blog/post/index.html")).unwrap().contains("Edited"));
+    }
+
+    #[test]
+    #[cfg(feature = "batch")]
+    fn test_batch_build_to_outside_out_dir() {
+        use crate::process::BuildStatus;
+
+        let dir = TempDir::new().unwrap();
+        let out = dir.path().join("out");
+        let page = dir.path().join("page.typ");
+        fs::write(&page, "= Page").unwrap();
+        let escape = dir.path().join("escape.html");
+
+        let batcher = Compiler::new(dir.path()).into_batch();
+        for mapped in [PathBuf::from("../escape.html"), escape.clone()] {
+            let manifest = batcher.build_to(&[&page], &out, |_| mapped.clone()).unwrap();
+            assert!(matches!(manifest.pages()[0].status, BuildStatus::Failed(_)));
+        }
+        assert!(!escape.exists());
+    }
+
+    #[test]
+    #[cfg(all(feature = "batch", feature = "svg"))]
+    fn test_batch_build_to_shared_frames() {
+        use crate::html::FrameMode;
+
+        let dir = TempDir::new().unwrap();
+        let out = dir.path().join("out");
+        let pages: Vec<_> = (0..8)
+            .map(|i| {
+                let page = dir.path().join(format!("page{i}.typ"));
+                let source = format!("#html.frame(rect(width: 4pt))\n#html.frame(rect(width: {}pt))", i + 5);
+                fs::write(&page, source).unwrap();
+                page
+            })
+            .collect();
+
+        let batcher = Compiler::new(dir.path())
+            .into_batch()
+            .with_frame_output(FrameMode::external("/frames/"), "frames");
+        let manifest = batcher
+            .build_to(&pages, &out, |path| path.with_extension("html"))
+            .unwrap();
+
+        assert!(manifest.is_success());
+        // The shared frame is written once, plus one frame per page
+        assert_eq!(manifest.assets().len(), 9);
+        for entry in manifest.assets() {
+            assert!(entry.output.exists());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "batch")]
+    fn test_batch_with_thread_pool() {
+        use crate::process::WithParallelism;
+        use std::sync::Mutex;
+
+        let dir = TempDir::new().unwrap();
+        let file1 = dir.path().join("test1.typ");
+        let file2 = dir.path().join("test2.typ");
+        fs::write(&file1, "= File One").unwrap();
+        fs::write(&file2, "= File Two").unwrap();
+
+        let pool = std::sync::Arc::new(rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap());
+        let threads = Mutex::new(Vec::new());
+        let results = Compiler::new(dir.path())
+            .into_batch()
+            .with_thread_pool(pool.clone())
+            .with_memory_limit(1 << 20, |_| 1 << 20)
+            .with_snapshot_from(&[&file1, &file2])
+            .unwrap()
+            .batch_compile_each(&[&file1, &file2], |_| {
+                threads.lock().unwrap().push(rayon::current_thread_index());
+            })
+            .unwrap();
+
+        assert!(results.iter().all(|r| r.is_ok()));
+        assert_eq!(*threads.lock().unwrap(), [Some(0), Some(0)]);
+    }
+
+    #[test]
+    #[cfg(feature = "batch")]
+    fn test_batch_failure_policy() {
+        use crate::process::{FailurePolicy, WithParallelism};
+
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("template.typ"), "#let page(body) = { panic(\"broken\") }").unwrap();
+        let files: Vec<_> = (0..6)
+            .map(|i| {
+                let file = dir.path().join(format!("page{i}.typ"));
+                fs::write(&file, format!("#import \"template.typ\": page\n#page[Page {i}]")).unwrap();
+                file
+            })
+            .collect();
+
+        // One thread, so files start in input order
+        let batcher = |policy| {
+            Compiler::new(dir.path())
+                .into_batch()
+                .with_max_threads(1)
+                .with_failure_policy(policy)
+        };
+        let count = |results: &[Result<CompileResult, CompileError>]| {
+            let skipped = results.iter().filter(|r| matches!(r, Err(e) if e.is_skipped())).count();
+            (results.len() - skipped, skipped)
+        };
+
+        let results = batcher(FailurePolicy::Continue).batch_compile(&files).unwrap();
+        assert_eq!(count(&results), (6, 0));
+
+        let results = batcher(FailurePolicy::FailFast).batch_compile(&files).unwrap();
+        assert_eq!(count(&results), (1, 5));
+        assert!(results[0].as_ref().unwrap_err().has_fatal_errors());
+
+        let results = batcher(FailurePolicy::MaxSameErrors(2)).batch_compile(&files).unwrap();
+        assert_eq!(count(&results), (2, 4));
+        assert!(results[5].as_ref().unwrap_err().is_skipped());
+    }
+
+    #[test]
+    #[cfg(all(feature = "batch", feature = "scan"))]
+    fn test_batch_events() {
+        use crate::process::{BatchEvent, FailurePolicy, WithParallelism};
+        use std::sync::{Arc, Mutex};
+
+        let dir = TempDir::new().unwrap();
+        let files: Vec<_> = ["= One", "#text(fill: red, weight: \"nope\")[x]", "= Three"]
+            .iter()
+            .enumerate()
+            .map(|(i, source)| {
+                let file = dir.path().join(format!("test{i}.typ"));
+                fs::write(&file, source).unwrap();
+                file
+            })
+            .collect();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let log = events.clone();
+        let batcher = Compiler::new(dir.path())
+            .into_batch()
+            .with_max_threads(1)
+            .with_failure_policy(FailurePolicy::FailFast)
+            .with_observer(move |event| {
+                let name = match event {
+                    BatchEvent::SnapshotLoaded { .. } => "loaded".to_string(),
+                    BatchEvent::ScanStarted { index, .. } => format!("scan {index}"),
+                    BatchEvent::ScanFinished { index, ok, .. } => format!("scanned {index} {ok}"),
+                    BatchEvent::CompileStarted { index, .. } => format!("compile {index}"),
+                    BatchEvent::CompileFinished { index, ok, .. } => format!("compiled {index} {ok}"),
+                    BatchEvent::Skipped { index, .. } => format!("skipped {index}"),
+                };
+                log.lock().unwrap().push(name);
+            })
+            .with_snapshot_from(&files)
+            .unwrap();
+
+        batcher.batch_scan(&files[..1]).unwrap();
+        batcher.batch_compile(&files).unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(
+            *events,
+            [
+                "loaded", "loaded", "loaded",
+                "scan 0", "scanned 0 true",
+                "compile 0", "compiled 0 true",
+                "compile 1", "compiled 1 false",
+                "skipped 2",
+            ]
+        );
+    }
+
     #[test]
     #[cfg(feature = "batch")]
     fn test_batch_with_snapshot_from() {
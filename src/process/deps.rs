@@ -0,0 +1,334 @@
+//! Reverse dependency graph aggregated from [`AccessedDeps`].
+//!
+//! Answers "which pages depend on `templates/base.typ`?" and
+//! "which pages use `@preview/cetz`?" for incremental rebuilds.
+//! With the `serde` feature, the graph can be persisted across restarts.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let results = batcher.batch_compile(&pages)?;
+//!
+//! let mut graph = DependencyGraph::new(root);
+//! graph.insert_results(&pages, &results);
+//!
+//! let affected = graph.dependents_transitive("templates/base.typ");
+//! let cetz_pages = graph.package_dependents("preview", "cetz");
+//!
+//! // Persist (requires `serde` feature)
+//! std::fs::write("deps.json", serde_json::to_vec(&graph)?)?;
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use crate::resource::file::PackageId;
+use crate::world::normalize_path;
+
+use super::session::AccessedDeps;
+
+/// Page → dependency edges with reverse indices.
+///
+/// Paths under the root are stored relative to it; queries accept either
+/// relative or absolute paths.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DependencyGraph {
+    root: PathBuf,
+    /// Page → files it accessed.
+    files: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+    /// Page → packages it accessed.
+    packages: BTreeMap<PathBuf, BTreeSet<PackageId>>,
+    /// File → pages that accessed it.
+    file_dependents: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+    /// Package → pages that accessed it.
+    #[cfg_attr(feature = "serde", serde(with = "package_entries"))]
+    package_dependents: BTreeMap<PackageId, BTreeSet<PathBuf>>,
+}
+
+impl DependencyGraph {
+    /// Create an empty graph for the given project root.
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: normalize_path(root.as_ref()),
+            ..Self::default()
+        }
+    }
+
+    /// Get the project root.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Record the dependencies of a page, replacing any previous entry.
+    pub fn insert(&mut self, page: impl AsRef<Path>, deps: &AccessedDeps) {
+        let page = self.relative(page.as_ref());
+        self.remove(&page);
+
+        let files: BTreeSet<_> = deps.files.iter().map(|f| self.relative(f)).collect();
+        for file in &files {
+            self.file_dependents.entry(file.clone()).or_default().insert(page.clone());
+        }
+        for pkg in &deps.packages {
+            self.package_dependents.entry(pkg.clone()).or_default().insert(page.clone());
+        }
+
+        self.files.insert(page.clone(), files);
+        self.packages.insert(page, deps.packages.iter().cloned().collect());
+    }
+
+    /// Record the dependencies of successful batch results.
+    ///
+    /// `paths` and `results` must be in the same order, as returned by
+    /// `batch_compile()` / `batch_scan()`. Failed results are skipped and
+    /// leave any previous entry for that page untouched.
+    pub fn insert_results<P, T, E>(&mut self, paths: &[P], results: &[Result<T, E>])
+    where
+        P: AsRef<Path>,
+        T: AsRef<AccessedDeps>,
+    {
+        for (path, result) in paths.iter().zip(results) {
+            if let Ok(result) = result {
+                self.insert(path, result.as_ref());
+            }
+        }
+    }
+
+    /// Remove a page and its edges. Returns `true` if it was present.
+    pub fn remove(&mut self, page: impl AsRef<Path>) -> bool {
+        let page = self.relative(page.as_ref());
+
+        let files = self.files.remove(&page);
+        for file in files.iter().flatten() {
+            remove_edge(&mut self.file_dependents, file, &page);
+        }
+
+        let packages = self.packages.remove(&page);
+        for pkg in packages.iter().flatten() {
+            remove_edge(&mut self.package_dependents, pkg, &page);
+        }
+
+        files.is_some()
+    }
+
+    /// Get all recorded pages.
+    pub fn pages(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+
+    /// Check if a page is recorded.
+    pub fn contains(&self, page: impl AsRef<Path>) -> bool {
+        self.files.contains_key(&self.relative(page.as_ref()))
+    }
+
+    /// Get the files a page depends on.
+    pub fn dependencies(&self, page: impl AsRef<Path>) -> impl Iterator<Item = &Path> {
+        self.files
+            .get(&self.relative(page.as_ref()))
+            .into_iter()
+            .flatten()
+            .map(PathBuf::as_path)
+    }
+
+    /// Get the packages a page depends on.
+    pub fn package_dependencies(&self, page: impl AsRef<Path>) -> impl Iterator<Item = &PackageId> {
+        self.packages.get(&self.relative(page.as_ref())).into_iter().flatten()
+    }
+
+    /// Get the pages that accessed `file` directly.
+    pub fn dependents(&self, file: impl AsRef<Path>) -> impl Iterator<Item = &Path> {
+        self.file_dependents
+            .get(&self.relative(file.as_ref()))
+            .into_iter()
+            .flatten()
+            .map(PathBuf::as_path)
+    }
+
+    /// Get the pages affected by a change to `file`, transitively.
+    ///
+    /// Follows reverse edges through pages that are themselves imported
+    /// by other pages. Pages whose dependency lists were recorded from a
+    /// full compilation are already transitive; this matters when pages
+    /// were recorded from partial results.
+    pub fn dependents_transitive(&self, file: impl AsRef<Path>) -> BTreeSet<PathBuf> {
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::from([self.relative(file.as_ref())]);
+
+        while let Some(current) = queue.pop_front() {
+            for page in self.file_dependents.get(&current).into_iter().flatten() {
+                if seen.insert(page.clone()) {
+                    queue.push_back(page.clone());
+                }
+            }
+        }
+
+        seen
+    }
+
+    /// Get the pages affected by changes to any of `files`, transitively.
+    pub fn affected_by<P: AsRef<Path>>(&self, files: &[P]) -> BTreeSet<PathBuf> {
+        files
+            .iter()
+            .flat_map(|f| self.dependents_transitive(f))
+            .collect()
+    }
+
+    /// Get the pages that use a package (any version), e.g. `("preview", "cetz")`.
+    pub fn package_dependents(&self, namespace: &str, name: &str) -> BTreeSet<&Path> {
+        self.package_dependents
+            .iter()
+            .filter(|(pkg, _)| pkg.namespace() == namespace && pkg.name() == name)
+            .flat_map(|(_, pages)| pages.iter().map(PathBuf::as_path))
+            .collect()
+    }
+
+    /// Get the pages that use an exact package version.
+    pub fn package_version_dependents(&self, package: &PackageId) -> impl Iterator<Item = &Path> {
+        self.package_dependents
+            .get(package)
+            .into_iter()
+            .flatten()
+            .map(PathBuf::as_path)
+    }
+
+    /// Strip the root prefix from paths under it.
+    fn relative(&self, path: &Path) -> PathBuf {
+        if let Ok(rel) = path.strip_prefix(&self.root) {
+            return rel.to_path_buf();
+        }
+        // Retry with symlinks resolved (e.g., `/tmp` vs `/private/tmp`)
+        if path.is_absolute()
+            && let Ok(canonical) = path.canonicalize()
+            && let Ok(rel) = canonical.strip_prefix(&self.root)
+        {
+            return rel.to_path_buf();
+        }
+        path.to_path_buf()
+    }
+}
+
+/// Remove `page` from `key`'s dependents, dropping the entry when empty.
+fn remove_edge<K: Ord>(index: &mut BTreeMap<K, BTreeSet<PathBuf>>, key: &K, page: &Path) {
+    if let Some(pages) = index.get_mut(key) {
+        pages.remove(page);
+        if pages.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+/// (De)serialize a package-keyed map as a list of pairs, since JSON
+/// object keys must be strings.
+#[cfg(feature = "serde")]
+mod package_entries {
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::path::PathBuf;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::resource::file::PackageId;
+
+    pub(super) fn serialize<S: Serializer>(
+        map: &BTreeMap<PackageId, BTreeSet<PathBuf>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(map)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<PackageId, BTreeSet<PathBuf>>, D::Error> {
+        let entries = Vec::<(PackageId, BTreeSet<PathBuf>)>::deserialize(deserializer)?;
+        Ok(entries.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::compile::Compiler;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn deps(files: &[&str]) -> AccessedDeps {
+        AccessedDeps {
+            files: files.iter().map(PathBuf::from).collect(),
+            packages: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_insert_replace_remove() {
+        let mut graph = DependencyGraph::new("/site");
+        graph.insert("/site/a.typ", &deps(&["/site/a.typ", "/site/templates/base.typ"]));
+        graph.insert("/site/b.typ", &deps(&["/site/b.typ", "/site/templates/base.typ"]));
+
+        let pages: Vec<_> = graph.dependents("templates/base.typ").collect();
+        assert_eq!(pages, [Path::new("a.typ"), Path::new("b.typ")]);
+
+        // Re-inserting replaces old edges
+        graph.insert("/site/b.typ", &deps(&["/site/b.typ"]));
+        assert_eq!(graph.dependents("/site/templates/base.typ").count(), 1);
+
+        assert!(graph.remove("a.typ"));
+        assert!(!graph.remove("a.typ"));
+        assert_eq!(graph.dependents("templates/base.typ").count(), 0);
+        assert!(graph.contains("b.typ"));
+    }
+
+    #[test]
+    fn test_dependents_transitive() {
+        let mut graph = DependencyGraph::new("/site");
+        // `index` imports the page `post`, which imports `base`
+        graph.insert("post.typ", &deps(&["post.typ", "base.typ"]));
+        graph.insert("index.typ", &deps(&["index.typ", "post.typ"]));
+        graph.insert("about.typ", &deps(&["about.typ"]));
+
+        let affected = graph.dependents_transitive("base.typ");
+        assert_eq!(
+            affected.into_iter().collect::<Vec<_>>(),
+            [PathBuf::from("index.typ"), PathBuf::from("post.typ")]
+        );
+        assert_eq!(graph.affected_by(&["about.typ", "base.typ"]).len(), 3);
+    }
+
+    #[test]
+    fn test_insert_results_from_compile() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path().join("base.typ");
+        let a = dir.path().join("a.typ");
+        let b = dir.path().join("b.typ");
+        fs::write(&base, "#let title = [Base]").unwrap();
+        fs::write(&a, "#import \"base.typ\": title\n= #title").unwrap();
+        fs::write(&b, "= B").unwrap();
+
+        let compiler = |path: &Path| Compiler::new(dir.path()).with_path(path).compile();
+        let paths = [&a, &b];
+        let results: Vec<_> = paths.iter().map(|p| compiler(p)).collect();
+
+        let mut graph = DependencyGraph::new(dir.path());
+        graph.insert_results(&paths, &results);
+
+        let pages: Vec<_> = graph.dependents(&base).collect();
+        assert_eq!(pages, [Path::new("a.typ")]);
+        assert_eq!(graph.pages().count(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde_roundtrip() {
+        let spec: typst::syntax::package::PackageSpec = "@preview/cetz:0.3.0".parse().unwrap();
+        let mut with_package = deps(&["a.typ", "base.typ"]);
+        with_package.packages.push(PackageId::from_spec(&spec));
+
+        let mut graph = DependencyGraph::new("/site");
+        graph.insert("a.typ", &with_package);
+        graph.insert("b.typ", &deps(&["b.typ"]));
+
+        let json = serde_json::to_string(&graph).unwrap();
+        let restored: DependencyGraph = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, graph);
+        assert_eq!(restored.dependents("base.typ").count(), 1);
+        assert_eq!(restored.package_dependents("preview", "cetz").len(), 1);
+    }
+}
@@ -0,0 +1,182 @@
+//! Structured progress events for batch processing.
+
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::diagnostic::CompileError;
+
+use super::compile::CompileResult;
+
+/// Progress event reported by [`Batcher`](super::batch::Batcher) and
+/// [`BatchScanner`](super::batch::BatchScanner).
+///
+/// `index` is the file's position in the paths passed to the batch call.
+/// Events of different files arrive interleaved and from several threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BatchEvent<'a> {
+    /// A content file was loaded into the snapshot.
+    SnapshotLoaded {
+        /// The loaded file.
+        path: &'a Path,
+    },
+    /// A file started scanning.
+    ScanStarted {
+        /// Position in the input paths.
+        index: usize,
+        /// The scanned file.
+        path: &'a Path,
+    },
+    /// A file finished scanning.
+    ScanFinished {
+        /// Position in the input paths.
+        index: usize,
+        /// The scanned file.
+        path: &'a Path,
+        /// Time spent on the file.
+        duration: Duration,
+        /// Whether the scan succeeded.
+        ok: bool,
+        /// Number of warnings (including those of a failed scan).
+        warning_count: usize,
+    },
+    /// A file started compiling.
+    CompileStarted {
+        /// Position in the input paths.
+        index: usize,
+        /// The compiled file.
+        path: &'a Path,
+    },
+    /// A file finished compiling.
+    CompileFinished {
+        /// Position in the input paths.
+        index: usize,
+        /// The compiled file.
+        path: &'a Path,
+        /// Time spent on the file.
+        duration: Duration,
+        /// Whether the compilation succeeded.
+        ok: bool,
+        /// Number of warnings (including those of a failed compilation).
+        warning_count: usize,
+    },
+    /// A file was skipped by the [`FailurePolicy`](super::FailurePolicy).
+    Skipped {
+        /// Position in the input paths.
+        index: usize,
+        /// The skipped file.
+        path: &'a Path,
+    },
+}
+
+impl BatchEvent<'_> {
+    /// Get the file the event is about.
+    pub fn path(&self) -> &Path {
+        match *self {
+            Self::SnapshotLoaded { path }
+            | Self::ScanStarted { path, .. }
+            | Self::ScanFinished { path, .. }
+            | Self::CompileStarted { path, .. }
+            | Self::CompileFinished { path, .. }
+            | Self::Skipped { path, .. } => path,
+        }
+    }
+
+    /// Check if the event completes a file (finished or skipped).
+    ///
+    /// Counting these gives the progress of a scan or compile call.
+    pub fn is_done(&self) -> bool {
+        matches!(
+            self,
+            Self::ScanFinished { .. } | Self::CompileFinished { .. } | Self::Skipped { .. }
+        )
+    }
+}
+
+type ObserverFn = dyn Fn(&BatchEvent<'_>) + Send + Sync;
+
+/// Optional event callback held by batch builders.
+#[derive(Clone, Default)]
+pub(crate) struct Observer(Option<Arc<ObserverFn>>);
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Observer").field(&self.0.is_some()).finish()
+    }
+}
+
+impl Observer {
+    pub(crate) fn new(f: impl Fn(&BatchEvent<'_>) + Send + Sync + 'static) -> Self {
+        Self(Some(Arc::new(f)))
+    }
+
+    pub(crate) fn emit(&self, event: BatchEvent<'_>) {
+        if let Some(f) = &self.0 {
+            f(&event);
+        }
+    }
+}
+
+/// What a batch call does with each file.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Task {
+    #[cfg(feature = "scan")]
+    Scan,
+    Compile,
+}
+
+impl Task {
+    pub(crate) fn started(self, index: usize, path: &Path) -> BatchEvent<'_> {
+        match self {
+            #[cfg(feature = "scan")]
+            Self::Scan => BatchEvent::ScanStarted { index, path },
+            Self::Compile => BatchEvent::CompileStarted { index, path },
+        }
+    }
+
+    pub(crate) fn finished<'a, T: Outcome>(
+        self,
+        index: usize,
+        path: &'a Path,
+        duration: Duration,
+        result: &Result<T, CompileError>,
+    ) -> BatchEvent<'a> {
+        let ok = result.is_ok();
+        let warning_count = match result {
+            Ok(output) => output.warning_count(),
+            Err(err) => err.diagnostics().map_or(0, |diags| diags.warning_count()),
+        };
+        match self {
+            #[cfg(feature = "scan")]
+            Self::Scan => BatchEvent::ScanFinished { index, path, duration, ok, warning_count },
+            Self::Compile => BatchEvent::CompileFinished { index, path, duration, ok, warning_count },
+        }
+    }
+}
+
+/// Per-file output of a batch call.
+pub(crate) trait Outcome {
+    fn warning_count(&self) -> usize;
+}
+
+impl Outcome for CompileResult {
+    fn warning_count(&self) -> usize {
+        self.diagnostics().warning_count()
+    }
+}
+
+#[cfg(feature = "scan")]
+impl Outcome for super::scan::ScanResult {
+    fn warning_count(&self) -> usize {
+        self.diagnostics().len()
+    }
+}
+
+#[cfg(feature = "pdf")]
+impl Outcome for super::paged::PagedResult {
+    fn warning_count(&self) -> usize {
+        self.diagnostics().warning_count()
+    }
+}
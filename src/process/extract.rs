@@ -0,0 +1,514 @@
+//! Extractor combinators and runtime-assembled extractor sets.
+//!
+//! - [`Filter`], [`Map`], [`Take`], [`Within`] - adapters created by the
+//!   provided methods on [`Extractor`]
+//! - [`ExtractorSet`] - type-erased extractors keyed by name, producing a
+//!   JSON object in a single traversal
+//!
+//! # Example
+//!
+//! ```ignore
+//! let external = LinkExtractor::new().filter(|link| link.is_external()).take(10);
+//!
+//! let set = ExtractorSet::new()
+//!     .with("external_links", external)
+//!     .with("appendix_images", ImageExtractor::new().within("appendix").unwrap());
+//! let json = result.extract(set);
+//! ```
+
+use std::ops::ControlFlow;
+
+use serde_json::{json, Value as JsonValue};
+use typst::foundations::{Content, Label};
+use typst::utils::PicoStr;
+
+use crate::diagnostic::{SourceLocation, SpanResolver};
+
+use super::scan::{
+    Bibliography, Citation, CitationSource, Extractor, Figure, Footnote, Heading, Image, Labeled,
+    Link, LinkSource, Raw,
+};
+
+/// Keeps only the items matching a predicate.
+///
+/// Created by [`Extractor::filter`].
+#[derive(Debug)]
+pub struct Filter<E, P> {
+    inner: E,
+    predicate: P,
+}
+
+impl<E, P> Filter<E, P> {
+    pub(crate) fn new(inner: E, predicate: P) -> Self {
+        Self { inner, predicate }
+    }
+}
+
+impl<E, P, T> Extractor for Filter<E, P>
+where
+    E: Extractor<Output = Vec<T>>,
+    P: FnMut(&T) -> bool,
+{
+    type Output = Vec<T>;
+
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        self.inner.visit(elem)
+    }
+
+    fn finish(mut self) -> Self::Output {
+        let mut items = self.inner.finish();
+        items.retain(|item| (self.predicate)(item));
+        items
+    }
+
+    fn set_resolver(&mut self, resolver: &SpanResolver) {
+        self.inner.set_resolver(resolver);
+    }
+}
+
+/// Transforms the output of an extractor.
+///
+/// Created by [`Extractor::map`].
+#[derive(Debug)]
+pub struct Map<E, F> {
+    inner: E,
+    f: F,
+}
+
+impl<E, F> Map<E, F> {
+    pub(crate) fn new(inner: E, f: F) -> Self {
+        Self { inner, f }
+    }
+}
+
+impl<E, F, U> Extractor for Map<E, F>
+where
+    E: Extractor,
+    F: FnOnce(E::Output) -> U,
+{
+    type Output = U;
+
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        self.inner.visit(elem)
+    }
+
+    fn finish(self) -> Self::Output {
+        (self.f)(self.inner.finish())
+    }
+
+    fn set_resolver(&mut self, resolver: &SpanResolver) {
+        self.inner.set_resolver(resolver);
+    }
+}
+
+/// Keeps at most `n` items.
+///
+/// Created by [`Extractor::take`].
+#[derive(Debug)]
+pub struct Take<E> {
+    inner: E,
+    n: usize,
+}
+
+impl<E> Take<E> {
+    pub(crate) fn new(inner: E, n: usize) -> Self {
+        Self { inner, n }
+    }
+}
+
+impl<E, T> Extractor for Take<E>
+where
+    E: Extractor<Output = Vec<T>>,
+{
+    type Output = Vec<T>;
+
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        self.inner.visit(elem)
+    }
+
+    fn finish(self) -> Self::Output {
+        let mut items = self.inner.finish();
+        items.truncate(self.n);
+        items
+    }
+
+    fn set_resolver(&mut self, resolver: &SpanResolver) {
+        self.inner.set_resolver(resolver);
+    }
+}
+
+/// Only visits elements inside elements with a given label.
+///
+/// The labeled element itself is included. In the Eval-only tree, headings
+/// do not contain their section, so label a container instead
+/// (e.g., `#block[...] <appendix>`).
+///
+/// Created by [`Extractor::within`].
+#[derive(Debug)]
+pub struct Within<E> {
+    inner: E,
+    label: Label,
+    /// Remaining outer visits that belong to an already visited subtree.
+    skip: usize,
+}
+
+impl<E> Within<E> {
+    pub(crate) fn new(inner: E, label: &str) -> Option<Self> {
+        Some(Self {
+            inner,
+            label: Label::new(PicoStr::intern(label))?,
+            skip: 0,
+        })
+    }
+}
+
+impl<E: Extractor> Extractor for Within<E> {
+    type Output = E::Output;
+
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        // Traversal is pre-order, so a subtree is a contiguous run of visits
+        if self.skip > 0 {
+            self.skip -= 1;
+            return ControlFlow::Continue(());
+        }
+
+        if elem.label() != Some(self.label) {
+            return ControlFlow::Continue(());
+        }
+
+        let mut visited = 0;
+        let flow = elem.traverse(&mut |child: Content| {
+            visited += 1;
+            self.inner.visit(&child)
+        });
+        self.skip = visited - 1;
+        flow
+    }
+
+    fn finish(self) -> Self::Output {
+        self.inner.finish()
+    }
+
+    fn set_resolver(&mut self, resolver: &SpanResolver) {
+        self.inner.set_resolver(resolver);
+    }
+}
+
+/// Conversion of extractor output into JSON, for [`DynExtractor`].
+pub trait IntoJson {
+    /// Convert into a JSON value.
+    fn into_json(self) -> JsonValue;
+}
+
+/// An object-safe [`Extractor`] producing JSON.
+///
+/// Implemented for every extractor whose output implements [`IntoJson`].
+pub trait DynExtractor {
+    /// Visit a content element during traversal.
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()>;
+
+    /// Finalize and return the extracted data as JSON.
+    fn finish_json(self: Box<Self>) -> JsonValue;
+
+    /// Receive the span resolver before traversal (see [`Extractor::set_resolver`]).
+    fn set_resolver(&mut self, resolver: &SpanResolver);
+}
+
+impl<E> DynExtractor for E
+where
+    E: Extractor,
+    E::Output: IntoJson,
+{
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        Extractor::visit(self, elem)
+    }
+
+    fn finish_json(self: Box<Self>) -> JsonValue {
+        Extractor::finish(*self).into_json()
+    }
+
+    fn set_resolver(&mut self, resolver: &SpanResolver) {
+        Extractor::set_resolver(self, resolver)
+    }
+}
+
+/// Named, type-erased extractors run in a single traversal.
+///
+/// Unlike tuples, the number and types of extractors can be decided at
+/// runtime (e.g., from plugin configuration). An extractor that stops early
+/// does not stop the others.
+///
+/// The output is a JSON object keyed by extractor name.
+#[derive(Default)]
+pub struct ExtractorSet {
+    extractors: Vec<Entry>,
+}
+
+struct Entry {
+    name: String,
+    extractor: Box<dyn DynExtractor>,
+    done: bool,
+}
+
+impl ExtractorSet {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an extractor under `name` (builder style).
+    pub fn with(mut self, name: impl Into<String>, extractor: impl DynExtractor + 'static) -> Self {
+        self.insert(name, Box::new(extractor));
+        self
+    }
+
+    /// Add a boxed extractor under `name`, replacing any with the same name.
+    pub fn insert(&mut self, name: impl Into<String>, extractor: Box<dyn DynExtractor>) {
+        let name = name.into();
+        self.extractors.retain(|entry| entry.name != name);
+        self.extractors.push(Entry {
+            name,
+            extractor,
+            done: false,
+        });
+    }
+
+    /// Number of extractors in the set.
+    pub fn len(&self) -> usize {
+        self.extractors.len()
+    }
+
+    /// Check if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.extractors.is_empty()
+    }
+}
+
+impl std::fmt::Debug for ExtractorSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.extractors.iter().map(|entry| &entry.name))
+            .finish()
+    }
+}
+
+impl Extractor for ExtractorSet {
+    type Output = serde_json::Map<String, JsonValue>;
+
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        let mut active = false;
+        for entry in self.extractors.iter_mut().filter(|entry| !entry.done) {
+            entry.done = entry.extractor.visit(elem).is_break();
+            active |= !entry.done;
+        }
+        if active {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(())
+        }
+    }
+
+    fn finish(self) -> Self::Output {
+        self.extractors
+            .into_iter()
+            .map(|entry| (entry.name, entry.extractor.finish_json()))
+            .collect()
+    }
+
+    fn set_resolver(&mut self, resolver: &SpanResolver) {
+        for entry in &mut self.extractors {
+            entry.extractor.set_resolver(resolver);
+        }
+    }
+}
+
+impl IntoJson for serde_json::Map<String, JsonValue> {
+    fn into_json(self) -> JsonValue {
+        JsonValue::Object(self)
+    }
+}
+
+impl IntoJson for JsonValue {
+    fn into_json(self) -> JsonValue {
+        self
+    }
+}
+
+impl<T: IntoJson> IntoJson for Vec<T> {
+    fn into_json(self) -> JsonValue {
+        JsonValue::Array(self.into_iter().map(IntoJson::into_json).collect())
+    }
+}
+
+impl<T: IntoJson> IntoJson for Option<T> {
+    fn into_json(self) -> JsonValue {
+        self.map_or(JsonValue::Null, IntoJson::into_json)
+    }
+}
+
+impl IntoJson for String {
+    fn into_json(self) -> JsonValue {
+        JsonValue::String(self)
+    }
+}
+
+impl IntoJson for usize {
+    fn into_json(self) -> JsonValue {
+        self.into()
+    }
+}
+
+impl IntoJson for bool {
+    fn into_json(self) -> JsonValue {
+        self.into()
+    }
+}
+
+impl IntoJson for Link {
+    fn into_json(self) -> JsonValue {
+        let source = match self.source {
+            LinkSource::Link => "link",
+            LinkSource::Href => "href",
+            LinkSource::Src => "src",
+            LinkSource::Image => "image",
+        };
+        json!({ "dest": self.dest, "source": source, "location": self.location.into_json() })
+    }
+}
+
+impl IntoJson for SourceLocation {
+    fn into_json(self) -> JsonValue {
+        json!({ "path": self.path, "line": self.line, "column": self.column })
+    }
+}
+
+impl IntoJson for Heading {
+    fn into_json(self) -> JsonValue {
+        json!({
+            "level": self.level,
+            "text": self.text,
+            "supplement": self.supplement,
+            "location": self.location.into_json(),
+        })
+    }
+}
+
+impl IntoJson for Figure {
+    fn into_json(self) -> JsonValue {
+        json!({ "kind": self.kind, "caption": self.caption, "alt": self.alt, "label": self.label })
+    }
+}
+
+impl IntoJson for Image {
+    fn into_json(self) -> JsonValue {
+        json!({ "path": self.path, "alt": self.alt, "width": self.width })
+    }
+}
+
+impl IntoJson for Raw {
+    fn into_json(self) -> JsonValue {
+        json!({ "lang": self.lang, "text": self.text, "block": self.block })
+    }
+}
+
+impl IntoJson for Footnote {
+    fn into_json(self) -> JsonValue {
+        json!({ "text": self.text, "reference": self.reference })
+    }
+}
+
+impl IntoJson for Citation {
+    fn into_json(self) -> JsonValue {
+        let source = match self.source {
+            CitationSource::Cite => "cite",
+            CitationSource::Ref => "ref",
+        };
+        json!({ "key": self.key, "source": source })
+    }
+}
+
+impl IntoJson for Bibliography {
+    fn into_json(self) -> JsonValue {
+        json!({ "sources": self.sources })
+    }
+}
+
+impl IntoJson for Labeled {
+    fn into_json(self) -> JsonValue {
+        json!({ "label": self.label, "kind": self.kind, "text": self.text })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::scan::{
+        HeadingExtractor, ImageExtractor, LinkExtractor, MetadataExtractor, Scanner,
+    };
+    use tempfile::TempDir;
+
+    const SOURCE: &str = r#"
+= Intro
+#link("https://a.com")[A] #link("/about")[About] #link("https://b.com")[B]
+#metadata((title: "Test")) <meta>
+#block[
+  == Extra
+  #link("https://c.com")[C]
+] <appendix>
+"#;
+
+    fn scan() -> crate::process::scan::ScanResult {
+        let dir = TempDir::new().unwrap();
+        Scanner::new(dir.path()).scan_source("test.typ", SOURCE).unwrap()
+    }
+
+    #[test]
+    fn test_combinators() {
+        let result = scan();
+
+        let external = result.extract(LinkExtractor::new().filter(|l: &Link| l.is_external()).take(2));
+        let dests: Vec<_> = external.iter().map(|l| l.dest.as_str()).collect();
+        assert_eq!(dests, ["https://a.com", "https://b.com"]);
+
+        let count = result.extract(HeadingExtractor::new().map(|h| h.len()));
+        assert_eq!(count, 2);
+
+        let (links, headings) = result.extract((
+            LinkExtractor::new().within("appendix").unwrap(),
+            HeadingExtractor::new().within("appendix").unwrap(),
+        ));
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].dest, "https://c.com");
+        assert_eq!(links[0].location.as_ref().unwrap().line, 7);
+        assert_eq!(headings[0].text, "Extra");
+        assert!(LinkExtractor::new().within("").is_none());
+    }
+
+    #[test]
+    fn test_extractor_set() {
+        let result = scan();
+
+        let mut set = ExtractorSet::new()
+            .with("links", LinkExtractor::new().filter(|l: &Link| l.is_site_root()))
+            // Stops the traversal on its own once found; must not stop the others
+            .with("meta", MetadataExtractor::new("meta").unwrap())
+            .with("headings", HeadingExtractor::new().map(|h| h.len()));
+        set.insert("images", Box::new(ImageExtractor::new()));
+        assert_eq!(set.len(), 4);
+
+        let json = result.extract(set);
+        assert_eq!(
+            JsonValue::Object(json),
+            json!({
+                "links": [{
+                    "dest": "/about",
+                    "source": "link",
+                    "location": { "path": "test.typ", "line": 3, "column": 28 },
+                }],
+                "meta": { "title": "Test" },
+                "headings": 2,
+                "images": [],
+            })
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! Shared `with_cancellation` / `with_timeout` methods via trait.
+
+use std::time::Duration;
+
+use crate::world::{CancellationToken, WorldBuilder};
+
+/// Cancellation and timeout settings held by compile/scan builders.
+#[derive(Debug, Clone, Default)]
+pub struct InterruptSettings {
+    token: Option<CancellationToken>,
+    timeout: Option<Duration>,
+}
+
+impl InterruptSettings {
+    /// Apply the settings to a world builder.
+    pub(crate) fn apply(&self, mut builder: WorldBuilder) -> WorldBuilder {
+        if let Some(token) = &self.token {
+            builder = builder.with_cancellation(token.clone());
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.with_timeout(timeout);
+        }
+        builder
+    }
+}
+
+/// Trait for types that support cooperative cancellation and timeouts.
+///
+/// Provides `with_cancellation` and `with_timeout` methods.
+pub trait WithInterrupt: Sized {
+    /// Get mutable reference to the interrupt settings.
+    fn interrupt_mut(&mut self) -> &mut InterruptSettings;
+
+    /// Abort compilation once `token` is cancelled.
+    ///
+    /// Interrupted compilations return [`CompileError::Cancelled`](crate::diagnostic::CompileError::Cancelled).
+    /// In batch mode the token is shared: files not yet started fail fast,
+    /// while the batch call itself still returns one result per file.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let token = CancellationToken::new();
+    /// let compiler = Compiler::new(root).with_cancellation(token.clone());
+    /// ```
+    fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.interrupt_mut().token = Some(token);
+        self
+    }
+
+    /// Abort compilation after `timeout` of wall-clock time.
+    ///
+    /// Interrupted compilations return [`CompileError::TimedOut`](crate::diagnostic::CompileError::TimedOut).
+    /// In batch mode the timeout applies to each file separately.
+    ///
+    /// Checks happen only on file access and before each phase. Evaluation
+    /// is not preempted, so a template that loops or recurses without
+    /// touching files overruns the timeout until its phase returns. A
+    /// result that completes past the deadline is still returned.
+    fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.interrupt_mut().timeout = Some(timeout);
+        self
+    }
+}
@@ -3,18 +3,58 @@
 //! - [`Compiler`] - Builder-based compilation API
 //! - [`Batcher`] - Batch compilation API for parallel processing
 //! - [`Scanner`] - Builder-based scanning API (Eval only, skips Layout)
+//! - [`Project`] - Long-lived incremental compilation for watch/serve mode
+//! - [`DependencyGraph`] - Reverse dependency graph from accessed files/packages
+//! - [`SearchIndex`] - Client-side full-text search index from compile/scan results
+//! - [`PagedResult`] - Paged compilation for PDF export (requires `pdf` feature)
 
 mod common;
+#[cfg(feature = "batch")]
+mod events;
+#[cfg(feature = "scan")]
+mod extract;
 mod inputs;
+mod interrupt;
+#[cfg(feature = "batch")]
+mod parallel;
+#[cfg(feature = "batch")]
+mod policy;
 mod session;
+mod stats;
+mod timestamp;
 pub mod compile;
+pub mod deps;
+pub mod project;
+pub mod search;
 #[cfg(feature = "batch")]
 pub mod batch;
+#[cfg(feature = "batch")]
+pub mod build;
 #[cfg(feature = "scan")]
 pub mod scan;
+#[cfg(feature = "scan")]
+pub mod syntax;
+#[cfg(feature = "pdf")]
+pub mod paged;
 
 pub use inputs::WithInputs;
+pub use interrupt::{InterruptSettings, WithInterrupt};
+#[cfg(feature = "batch")]
+pub use events::BatchEvent;
+#[cfg(feature = "batch")]
+pub use parallel::{ParallelSettings, WithParallelism};
+#[cfg(feature = "batch")]
+pub use policy::FailurePolicy;
+pub use deps::DependencyGraph;
+pub use project::{Project, ProjectUpdate};
+pub use search::{SearchIndex, SearchOptions, SearchPage, SearchSection, Searchable};
 pub use session::{AccessedDeps, CompileSession};
+pub use stats::{BatchReport, CompileStats};
+pub use timestamp::{TimestampSettings, WithTimestamp};
 
 #[cfg(feature = "batch")]
 pub use batch::{Batcher, BatchScanner};
+#[cfg(feature = "batch")]
+pub use build::{pretty_html_path, BuildEntry, BuildManifest, BuildStatus};
+#[cfg(feature = "pdf")]
+pub use paged::PagedResult;
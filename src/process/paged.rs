@@ -0,0 +1,243 @@
+//! Paged compilation API for PDF export.
+//!
+//! Compiles the same sources as the HTML pipeline into a [`PagedDocument`],
+//! reusing shared fonts, snapshots, `sys.inputs` and diagnostics.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use typst_batch::Compiler;
+//! use std::path::Path;
+//!
+//! let result = Compiler::new(Path::new("."))
+//!     .with_path(Path::new("doc.typ"))
+//!     .compile_paged()?;
+//!
+//! std::fs::write("doc.pdf", result.pdf()?)?;
+//! ```
+
+use std::path::PathBuf;
+use std::time::Instant;
+
+use typst::layout::PagedDocument;
+use typst_pdf::PdfOptions;
+
+use crate::diagnostic::{has_errors, CompileError, Diagnostics};
+use crate::resource::file::PackageId;
+use crate::world::TypstWorld;
+
+use super::common::timed_eval;
+use super::session::{AccessedDeps, CompileSession};
+use super::stats::{CompileStats, ExportTimer};
+
+/// Result of a successful paged compilation.
+#[derive(Debug)]
+pub struct PagedResult {
+    document: PagedDocument,
+    accessed: AccessedDeps,
+    diagnostics: Diagnostics,
+    stats: CompileStats,
+    export: ExportTimer,
+}
+
+impl PagedResult {
+    /// Get the compiled paged document.
+    pub fn document(&self) -> &PagedDocument {
+        &self.document
+    }
+
+    /// Get the number of pages in the document.
+    pub fn page_count(&self) -> usize {
+        self.document.pages.len()
+    }
+
+    /// Export the document to PDF bytes with default options.
+    pub fn pdf(&self) -> Result<Vec<u8>, CompileError> {
+        self.pdf_with(&PdfOptions::default())
+    }
+
+    /// Export the document to PDF bytes with custom options.
+    ///
+    /// Use this to set PDF standards (e.g., PDF/A), page ranges or a
+    /// fixed creation timestamp.
+    ///
+    /// The export time is added to [`stats().export`](CompileStats::export).
+    pub fn pdf_with(&self, options: &PdfOptions) -> Result<Vec<u8>, CompileError> {
+        let start = Instant::now();
+        let pdf = typst_pdf::pdf(&self.document, options).map_err(|errors| {
+            let message = errors
+                .iter()
+                .map(|e| e.message.as_str())
+                .collect::<Vec<_>>()
+                .join("; ");
+            CompileError::pdf_export(message)
+        });
+        self.export.record(start.elapsed());
+        pdf
+    }
+
+    /// Get per-phase timing and file access statistics.
+    pub fn stats(&self) -> CompileStats {
+        CompileStats {
+            export: self.export.get(),
+            ..self.stats
+        }
+    }
+
+    /// Get files and packages accessed during compilation.
+    pub fn accessed(&self) -> &AccessedDeps {
+        &self.accessed
+    }
+
+    /// Get files accessed during compilation.
+    pub fn accessed_files(&self) -> &[PathBuf] {
+        &self.accessed.files
+    }
+
+    /// Get packages accessed during compilation.
+    pub fn accessed_packages(&self) -> &[PackageId] {
+        &self.accessed.packages
+    }
+
+    /// Get compilation diagnostics (warnings).
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// Take ownership of the document.
+    pub fn into_document(self) -> PagedDocument {
+        self.document
+    }
+
+    /// Destructure into components.
+    pub fn into_parts(self) -> (PagedDocument, AccessedDeps, Diagnostics) {
+        (self.document, self.accessed, self.diagnostics)
+    }
+}
+
+impl AsRef<AccessedDeps> for PagedResult {
+    fn as_ref(&self) -> &AccessedDeps {
+        &self.accessed
+    }
+}
+
+/// Internal paged compile implementation, shared by single and batch APIs.
+///
+/// The world should be built with [`WorldBuilder::paged`](crate::world::WorldBuilder::paged)
+/// so that the HTML feature is disabled.
+pub(crate) fn compile_paged_with_world(world: &TypstWorld) -> Result<PagedResult, CompileError> {
+    world.check_interrupt()?;
+    let start = Instant::now();
+    let session = CompileSession::start();
+    let line_offset = world.prelude_line_count();
+
+    let eval = timed_eval(world);
+    world.check_interrupt()?;
+
+    let layout_start = Instant::now();
+    let result = typst::compile::<PagedDocument>(world);
+    let layout = layout_start.elapsed();
+    if result.output.is_err() || has_errors(&result.warnings) {
+        // Errors may stem from file accesses refused by the interrupt
+        world.check_interrupt()?;
+    }
+
+    if has_errors(&result.warnings) {
+        return Err(CompileError::compilation_with_offset(world, result.warnings.to_vec(), line_offset));
+    }
+
+    let document = result.output.map_err(|errors| {
+        let all_diags: Vec<_> = errors.iter().chain(&result.warnings).cloned().collect();
+        CompileError::compilation_with_offset(world, all_diags, line_offset)
+    })?;
+
+    let accessed = session.finish(world.root());
+    let diagnostics = Diagnostics::resolve_with_offset(world, &result.warnings, line_offset);
+
+    let stats = CompileStats {
+        eval,
+        layout,
+        total: start.elapsed(),
+        files: world.file_stats(),
+        ..Default::default()
+    };
+
+    Ok(PagedResult {
+        document,
+        accessed,
+        diagnostics,
+        stats,
+        export: ExportTimer::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::process::compile::Compiler;
+    use crate::process::inputs::WithInputs;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compile_paged() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("test.typ");
+        fs::write(&file, "= Page One\n#pagebreak()\n= Page Two").unwrap();
+
+        let result = Compiler::new(dir.path())
+            .with_path(&file)
+            .compile_paged()
+            .unwrap();
+
+        assert_eq!(result.page_count(), 2);
+        let pdf = result.pdf().unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
+    }
+
+    #[test]
+    fn test_compile_paged_with_inputs() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("test.typ");
+        fs::write(
+            &file,
+            r#"#metadata(sys.inputs.at("title", default: none)) <title>"#,
+        )
+        .unwrap();
+
+        let result = Compiler::new(dir.path())
+            .with_inputs([("title", "Paged")])
+            .with_path(&file)
+            .compile_paged()
+            .unwrap();
+
+        let introspector = &result.document().introspector;
+        let query = |name: &str| {
+            let label = typst::foundations::Label::new(typst::utils::PicoStr::intern(name)).unwrap();
+            let elem = introspector
+                .query_unique(&typst::foundations::Selector::Label(label))
+                .unwrap();
+            let meta = elem.to_packed::<typst::introspection::MetadataElem>().unwrap();
+            serde_json::to_value(&meta.value).unwrap()
+        };
+        assert_eq!(query("title"), "Paged");
+    }
+
+    #[test]
+    #[cfg(feature = "batch")]
+    fn test_batch_compile_paged() {
+        let dir = TempDir::new().unwrap();
+        let file1 = dir.path().join("a.typ");
+        let file2 = dir.path().join("b.typ");
+        fs::write(&file1, "= A").unwrap();
+        fs::write(&file2, "#undefined").unwrap();
+
+        let results = Compiler::new(dir.path())
+            .into_batch()
+            .batch_compile_paged(&[&file1, &file2])
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].as_ref().unwrap().pdf().is_ok());
+        assert!(results[1].is_err());
+    }
+}
@@ -0,0 +1,308 @@
+//! Shared `with_thread_pool` / `with_max_threads` / `with_memory_limit` methods via trait.
+
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::{Condvar, Mutex, MutexGuard};
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder, Yield};
+
+use crate::diagnostic::CompileError;
+
+/// Thread pool and concurrency settings held by batch builders.
+#[derive(Debug, Clone, Default)]
+pub struct ParallelSettings {
+    pool: Option<Arc<ThreadPool>>,
+    max_threads: Option<usize>,
+    /// Pool built from `max_threads`, shared by clones and reused across calls.
+    built: Arc<Mutex<Option<Arc<ThreadPool>>>>,
+    memory: Option<Arc<MemoryBudget>>,
+}
+
+impl ParallelSettings {
+    /// Get the thread pool to run on, or `None` for rayon's global pool.
+    pub(crate) fn pool(&self) -> Result<Option<Arc<ThreadPool>>, CompileError> {
+        if let Some(pool) = &self.pool {
+            return Ok(Some(pool.clone()));
+        }
+        let Some(threads) = self.max_threads else {
+            return Ok(None);
+        };
+
+        let mut built = self.built.lock();
+        if built.is_none() {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .thread_name(|i| format!("typst-batch-{i}"))
+                .build()
+                .map_err(|e| CompileError::Io(std::io::Error::other(e)))?;
+            *built = Some(Arc::new(pool));
+        }
+        Ok(built.clone())
+    }
+
+    /// Run `f` on the configured pool.
+    pub(crate) fn install<R: Send>(&self, f: impl FnOnce() -> R + Send) -> Result<R, CompileError> {
+        Ok(match self.pool()? {
+            Some(pool) => pool.install(f),
+            None => f(),
+        })
+    }
+
+    /// Run `f` for each path on the configured pool, keeping input order.
+    ///
+    /// With a memory limit, each file is admitted on the calling thread
+    /// before it is spawned, and its reservation is held until `f` returns.
+    /// Workers never wait for memory: a worker may be running part of
+    /// another file's compilation, which would wait on it in turn.
+    pub(crate) fn map_paths<P, R>(
+        &self,
+        paths: &[P],
+        f: impl Fn(usize, &Path) -> R + Sync,
+    ) -> Result<Vec<R>, CompileError>
+    where
+        P: AsRef<Path> + Sync,
+        R: Send,
+    {
+        let Some(budget) = &self.memory else {
+            return self.install(|| {
+                paths
+                    .par_iter()
+                    .enumerate()
+                    .map(|(index, path)| f(index, path.as_ref()))
+                    .collect()
+            });
+        };
+
+        let slots: Vec<Mutex<Option<R>>> = paths.iter().map(|_| Mutex::new(None)).collect();
+        match self.pool()? {
+            Some(pool) => pool.in_place_scope(|scope| budget.spawn_each(scope, paths, &f, &slots)),
+            None => rayon::in_place_scope(|scope| budget.spawn_each(scope, paths, &f, &slots)),
+        }
+        Ok(slots.into_iter().map(|slot| slot.into_inner().expect("every path is run")).collect())
+    }
+}
+
+/// Trait for batch builders that run on a configurable thread pool.
+///
+/// Provides `with_thread_pool`, `with_max_threads` and `with_memory_limit` methods.
+pub trait WithParallelism: Sized {
+    /// Get mutable reference to the parallel settings.
+    fn parallel_mut(&mut self) -> &mut ParallelSettings;
+
+    /// Run batch work (including snapshot loading) on a dedicated pool
+    /// instead of rayon's global pool.
+    ///
+    /// Takes precedence over [`with_max_threads`](Self::with_max_threads).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let pool = Arc::new(rayon::ThreadPoolBuilder::new().num_threads(4).build()?);
+    /// let batcher = Batcher::new(root).with_thread_pool(pool);
+    /// ```
+    fn with_thread_pool(mut self, pool: Arc<ThreadPool>) -> Self {
+        self.parallel_mut().pool = Some(pool);
+        self
+    }
+
+    /// Run batch work on a private pool with at most `threads` threads.
+    ///
+    /// The pool is created on first use and reused by later batch calls.
+    fn with_max_threads(mut self, threads: usize) -> Self {
+        let parallel = self.parallel_mut();
+        parallel.max_threads = Some(threads.max(1));
+        parallel.built = Arc::default();
+        self
+    }
+
+    /// Limit how many documents are processed at once by estimated memory.
+    ///
+    /// Before a file is processed, `estimate` returns its expected peak
+    /// memory in bytes, and the file is not started until the sum over
+    /// running files stays within `limit`. A file estimated above `limit`
+    /// runs alone. Files are admitted in input order by the calling thread,
+    /// so heavy documents reduce parallelism instead of exhausting memory.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Books are heavy, everything else is cheap
+    /// let batcher = Batcher::new(root).with_memory_limit(8 << 30, |path| {
+    ///     if path.starts_with("books") { 2 << 30 } else { 64 << 20 }
+    /// });
+    /// ```
+    fn with_memory_limit<F>(mut self, limit: u64, estimate: F) -> Self
+    where
+        F: Fn(&Path) -> u64 + Send + Sync + 'static,
+    {
+        self.parallel_mut().memory = Some(Arc::new(MemoryBudget {
+            limit,
+            estimate: Box::new(estimate),
+            used: Mutex::new(0),
+            freed: Condvar::new(),
+        }));
+        self
+    }
+}
+
+/// Shared byte budget for [`WithParallelism::with_memory_limit`].
+struct MemoryBudget {
+    limit: u64,
+    estimate: Box<dyn Fn(&Path) -> u64 + Send + Sync>,
+    used: Mutex<u64>,
+    freed: Condvar,
+}
+
+impl fmt::Debug for MemoryBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryBudget")
+            .field("limit", &self.limit)
+            .field("used", &*self.used.lock())
+            .finish_non_exhaustive()
+    }
+}
+
+impl MemoryBudget {
+    /// Admit each path in turn, then run `f` for it on `scope`.
+    fn spawn_each<'s, P, R>(
+        &'s self,
+        scope: &rayon::Scope<'s>,
+        paths: &'s [P],
+        f: &'s (impl Fn(usize, &Path) -> R + Sync),
+        slots: &'s [Mutex<Option<R>>],
+    ) where
+        P: AsRef<Path> + Sync,
+        R: Send,
+    {
+        for (index, path) in paths.iter().enumerate() {
+            let permit = self.reserve(path.as_ref());
+            scope.spawn(move |_| {
+                let result = f(index, path.as_ref());
+                drop(permit);
+                *slots[index].lock() = Some(result);
+            });
+        }
+    }
+
+    /// Wait until the budget admits `path`.
+    fn reserve(&self, path: &Path) -> MemoryPermit<'_> {
+        let bytes = (self.estimate)(path).min(self.limit);
+        let mut used = self.used.lock();
+        while *used + bytes > self.limit {
+            if rayon::current_thread_index().is_none() {
+                self.freed.wait(&mut used);
+                continue;
+            }
+            // Called from a pool worker: run queued files instead of
+            // blocking a thread they may need
+            let yielded = MutexGuard::unlocked(&mut used, rayon::yield_now);
+            if yielded != Some(Yield::Executed) {
+                self.freed.wait_for(&mut used, Duration::from_millis(1));
+            }
+        }
+        *used += bytes;
+        MemoryPermit { budget: self, bytes }
+    }
+}
+
+/// Reservation in a memory budget, released on drop.
+struct MemoryPermit<'a> {
+    budget: &'a MemoryBudget,
+    bytes: u64,
+}
+
+impl Drop for MemoryPermit<'_> {
+    fn drop(&mut self) {
+        *self.budget.used.lock() -= self.bytes;
+        self.budget.freed.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    struct Settings(ParallelSettings);
+
+    impl WithParallelism for Settings {
+        fn parallel_mut(&mut self) -> &mut ParallelSettings {
+            &mut self.0
+        }
+    }
+
+    #[test]
+    fn test_max_threads_pool_is_reused() {
+        let settings = Settings(ParallelSettings::default()).with_max_threads(2).0;
+        let threads = settings.install(rayon::current_num_threads).unwrap();
+        assert_eq!(threads, 2);
+
+        let first = settings.pool().unwrap().unwrap();
+        let second = settings.clone().pool().unwrap().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        assert!(ParallelSettings::default().pool().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_memory_limit_caps_concurrency() {
+        let settings = Settings(ParallelSettings::default())
+            .with_max_threads(4)
+            .with_memory_limit(100, |path| if path.starts_with("heavy") { 60 } else { 10 })
+            .0;
+        let running = AtomicU64::new(0);
+        let peak = AtomicU64::new(0);
+
+        let paths: Vec<_> = (0..16)
+            .map(|i| if i % 2 == 0 { format!("heavy/{i}") } else { format!("light/{i}") })
+            .collect();
+        let indices = settings
+            .map_paths(&paths, |index, path| {
+                let cost = if path.starts_with("heavy") { 60 } else { 10 };
+                let now = running.fetch_add(cost, Ordering::SeqCst) + cost;
+                peak.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(std::time::Duration::from_millis(2));
+                running.fetch_sub(cost, Ordering::SeqCst);
+                index
+            })
+            .unwrap();
+
+        assert_eq!(indices, (0..16).collect::<Vec<_>>());
+        assert!(peak.load(Ordering::SeqCst) <= 100);
+        assert_eq!(*settings.memory.as_ref().unwrap().used.lock(), 0);
+    }
+
+    #[test]
+    fn test_memory_limit_nested_parallelism() {
+        // Each file fits only alone, and its work joins on the same pool
+        // (like typst's parallel layout), so workers steal each other's jobs
+        let settings = Settings(ParallelSettings::default())
+            .with_max_threads(3)
+            .with_memory_limit(100, |_| 100)
+            .0;
+        let running = AtomicU64::new(0);
+        let paths: Vec<_> = (0..12).map(|i| format!("file/{i}")).collect();
+
+        let sums = settings
+            .map_paths(&paths, |index, _| {
+                assert_eq!(running.fetch_add(1, Ordering::SeqCst), 0);
+                let sum = (0..64u64)
+                    .into_par_iter()
+                    .map(|i| {
+                        std::thread::sleep(std::time::Duration::from_micros(100));
+                        i
+                    })
+                    .sum::<u64>();
+                running.fetch_sub(1, Ordering::SeqCst);
+                sum + index as u64
+            })
+            .unwrap();
+
+        assert_eq!(sums.len(), 12);
+        assert_eq!(sums[11], 2016 + 11);
+        assert_eq!(*settings.memory.as_ref().unwrap().used.lock(), 0);
+    }
+}
@@ -0,0 +1,142 @@
+//! Error budgets for batch compilation.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
+
+use crate::diagnostic::CompileError;
+
+/// When a batch stops compiling after errors.
+///
+/// Once the budget is exhausted, files that have not started yet return
+/// [`CompileError::Skipped`]; files already compiling still finish. Only
+/// fatal errors count, not cancellations, timeouts or skipped files.
+///
+/// # Example
+///
+/// ```ignore
+/// // A broken template fails every page with the same error: stop after 3
+/// let batcher = Batcher::new(root).with_failure_policy(FailurePolicy::MaxSameErrors(3));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Compile every file regardless of errors.
+    #[default]
+    Continue,
+    /// Stop after the first error.
+    FailFast,
+    /// Stop after this many errors in total.
+    MaxErrors(usize),
+    /// Stop after this many errors with the same root cause.
+    ///
+    /// Errors share a root cause when their first error diagnostic has the
+    /// same message at the same source position (e.g. a line in a shared
+    /// template), regardless of the page that triggered it.
+    MaxSameErrors(usize),
+}
+
+/// Error counts of one batch call under a [`FailurePolicy`].
+#[derive(Debug, Default)]
+pub(crate) struct ErrorBudget {
+    policy: FailurePolicy,
+    exhausted: AtomicBool,
+    counts: Mutex<(usize, FxHashMap<String, usize>)>,
+}
+
+impl ErrorBudget {
+    pub(crate) fn new(policy: FailurePolicy) -> Self {
+        Self {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    /// Check if remaining files should be skipped.
+    pub(crate) fn is_exhausted(&self) -> bool {
+        self.exhausted.load(Ordering::Relaxed)
+    }
+
+    /// Count a finished file's result against the budget.
+    pub(crate) fn record<T>(&self, result: &Result<T, CompileError>) {
+        let Err(err) = result else { return };
+        if matches!(self.policy, FailurePolicy::Continue)
+            || err.is_interrupted()
+            || err.is_skipped()
+            || !err.has_fatal_errors()
+        {
+            return;
+        }
+
+        let mut counts = self.counts.lock();
+        let (total, by_cause) = &mut *counts;
+        *total += 1;
+        let exhausted = match self.policy {
+            FailurePolicy::Continue => false,
+            FailurePolicy::FailFast => true,
+            FailurePolicy::MaxErrors(max) => *total >= max,
+            FailurePolicy::MaxSameErrors(max) => {
+                let same = by_cause.entry(root_cause(err)).or_default();
+                *same += 1;
+                *same >= max
+            }
+        };
+        if exhausted {
+            self.exhausted.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Position and message of the first error, or the error message.
+fn root_cause(err: &CompileError) -> String {
+    let first = err.diagnostics().and_then(|diags| diags.errors().next());
+    match first {
+        Some(info) => format!(
+            "{}:{}:{}: {}",
+            info.path.as_deref().unwrap_or(""),
+            info.line.unwrap_or(0),
+            info.column.unwrap_or(0),
+            info.message
+        ),
+        None => err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(message: &str) -> Result<(), CompileError> {
+        Err(CompileError::html_export(message))
+    }
+
+    #[test]
+    fn test_budgets() {
+        let budget = ErrorBudget::new(FailurePolicy::Continue);
+        for _ in 0..10 {
+            budget.record(&error("a"));
+        }
+        assert!(!budget.is_exhausted());
+
+        let budget = ErrorBudget::new(FailurePolicy::FailFast);
+        budget.record(&Ok(()));
+        budget.record(&Err::<(), _>(CompileError::Cancelled));
+        assert!(!budget.is_exhausted());
+        budget.record(&error("a"));
+        assert!(budget.is_exhausted());
+
+        let budget = ErrorBudget::new(FailurePolicy::MaxErrors(2));
+        budget.record(&error("a"));
+        assert!(!budget.is_exhausted());
+        budget.record(&error("b"));
+        assert!(budget.is_exhausted());
+
+        let budget = ErrorBudget::new(FailurePolicy::MaxSameErrors(2));
+        budget.record(&error("a"));
+        budget.record(&error("b"));
+        budget.record(&Err::<(), _>(CompileError::Skipped));
+        assert!(!budget.is_exhausted());
+        budget.record(&error("b"));
+        assert!(budget.is_exhausted());
+    }
+}
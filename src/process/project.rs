@@ -0,0 +1,318 @@
+//! Long-lived incremental compilation for watch/serve mode.
+//!
+//! A [`Project`] owns its file cache (independent of `GLOBAL_FILE_CACHE`),
+//! remembers which files each page accessed, and on [`Project::changed`]
+//! invalidates only the affected cache entries. [`Project::recompile`]
+//! then compiles only the pages whose dependencies changed.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut project = Project::new(root).with_inputs([("mode", "serve")]);
+//! project.add_pages(&pages);
+//!
+//! // Initial build: every page is compiled
+//! let update = project.recompile();
+//!
+//! // File watcher event
+//! project.changed(&[root.join("templates/base.typ")]);
+//! let update = project.recompile();
+//! for (path, result) in &update.compiled { /* write output */ }
+//! println!("{} pages up to date", update.skipped.len());
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rustc_hash::FxHashSet;
+use typst::foundations::Dict;
+
+use crate::diagnostic::CompileError;
+use crate::resource::file::file_id_from_path;
+use crate::world::{normalize_path, LocalCache, TypstWorld};
+
+use super::compile::{compile_with_world, CompileResult};
+use super::inputs::WithInputs;
+use super::interrupt::{InterruptSettings, WithInterrupt};
+use super::timestamp::{TimestampSettings, WithTimestamp};
+
+/// A page tracked by a [`Project`].
+struct Page {
+    path: PathBuf,
+    /// Files accessed by the last successful compilation.
+    /// `None` means the page must be compiled on the next `recompile()`.
+    deps: Option<FxHashSet<PathBuf>>,
+}
+
+/// Long-lived compilation session with dependency-based invalidation.
+///
+/// Pages whose last compilation failed stay dirty and are retried on
+/// every `recompile()`, since their dependencies are unknown.
+pub struct Project {
+    root: PathBuf,
+    cache: Arc<LocalCache>,
+    inputs: Option<Dict>,
+    preludes: Vec<String>,
+    postludes: Vec<String>,
+    interrupt: InterruptSettings,
+    timestamp: TimestampSettings,
+    pages: Vec<Page>,
+}
+
+impl WithInputs for Project {
+    fn inputs_mut(&mut self) -> &mut Option<Dict> {
+        &mut self.inputs
+    }
+}
+
+impl WithInterrupt for Project {
+    fn interrupt_mut(&mut self) -> &mut InterruptSettings {
+        &mut self.interrupt
+    }
+}
+
+impl WithTimestamp for Project {
+    fn timestamp_mut(&mut self) -> &mut TimestampSettings {
+        &mut self.timestamp
+    }
+}
+
+impl Project {
+    /// Create a new project with the given root directory.
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: normalize_path(root.as_ref()),
+            cache: Arc::new(LocalCache::new()),
+            inputs: None,
+            preludes: Vec::new(),
+            postludes: Vec::new(),
+            interrupt: InterruptSettings::default(),
+            timestamp: TimestampSettings::default(),
+            pages: Vec::new(),
+        }
+    }
+
+    /// Add prelude code to inject at the beginning of each page.
+    pub fn with_prelude(mut self, prelude: impl Into<String>) -> Self {
+        self.preludes.push(prelude.into());
+        self
+    }
+
+    /// Add postlude code to inject at the end of each page.
+    pub fn with_postlude(mut self, postlude: impl Into<String>) -> Self {
+        self.postludes.push(postlude.into());
+        self
+    }
+
+    /// Get the project root directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Track pages for compilation. Already tracked pages are ignored.
+    ///
+    /// New pages are compiled on the next `recompile()`.
+    pub fn add_pages<P: AsRef<Path>>(&mut self, paths: &[P]) {
+        for path in paths {
+            let path = normalize_path(path.as_ref());
+            if !self.pages.iter().any(|p| p.path == path) {
+                self.pages.push(Page { path, deps: None });
+            }
+        }
+    }
+
+    /// Stop tracking a page.
+    pub fn remove_page(&mut self, path: impl AsRef<Path>) {
+        let path = normalize_path(path.as_ref());
+        self.pages.retain(|p| p.path != path);
+    }
+
+    /// Get tracked pages in insertion order.
+    pub fn pages(&self) -> impl Iterator<Item = &Path> {
+        self.pages.iter().map(|p| p.path.as_path())
+    }
+
+    /// Get the files a page accessed during its last successful compilation.
+    ///
+    /// Returns `None` if the page is not tracked or needs recompilation.
+    pub fn deps(&self, path: impl AsRef<Path>) -> Option<&FxHashSet<PathBuf>> {
+        let path = normalize_path(path.as_ref());
+        self.pages.iter().find(|p| p.path == path)?.deps.as_ref()
+    }
+
+    /// Notify the project that files changed on disk.
+    ///
+    /// Invalidates the cache entries of these files and marks every page
+    /// that depends on them for recompilation.
+    pub fn changed<P: AsRef<Path>>(&mut self, paths: &[P]) {
+        let changed: FxHashSet<PathBuf> = paths.iter().map(|p| normalize_path(p.as_ref())).collect();
+
+        for path in &changed {
+            if let Some(id) = file_id_from_path(path, &self.root) {
+                self.cache.invalidate(id);
+            }
+        }
+
+        for page in &mut self.pages {
+            let affected = page
+                .deps
+                .as_ref()
+                .is_some_and(|deps| deps.iter().any(|d| changed.contains(d)));
+            if affected || changed.contains(&page.path) {
+                page.deps = None;
+            }
+        }
+    }
+
+    /// Drop all cached files and mark every page for recompilation.
+    pub fn invalidate_all(&mut self) {
+        self.cache.clear();
+        for page in &mut self.pages {
+            page.deps = None;
+        }
+    }
+
+    /// Compile all pages marked by `add_pages()` or `changed()`.
+    ///
+    /// Results are returned in page order; up-to-date pages are listed in
+    /// [`ProjectUpdate::skipped`].
+    pub fn recompile(&mut self) -> ProjectUpdate {
+        let dirty: Vec<usize> = (0..self.pages.len()).filter(|&i| self.pages[i].deps.is_none()).collect();
+        let skipped = self.pages.iter().filter(|p| p.deps.is_some()).map(|p| p.path.clone()).collect();
+
+        let results = self.compile_pages(&dirty);
+
+        let mut compiled = Vec::with_capacity(dirty.len());
+        for (index, result) in dirty.into_iter().zip(results) {
+            let page = &mut self.pages[index];
+            if let Ok(result) = &result {
+                page.deps = Some(result.accessed_files().iter().map(|f| normalize_path(f)).collect());
+            }
+            compiled.push((page.path.clone(), result));
+        }
+
+        ProjectUpdate { compiled, skipped }
+    }
+
+    /// Compile the pages at `indices`, in parallel with the `batch` feature.
+    fn compile_pages(&self, indices: &[usize]) -> Vec<Result<CompileResult, CompileError>> {
+        let compile = |&index: &usize| self.compile_page(&self.pages[index].path);
+
+        #[cfg(feature = "batch")]
+        {
+            use rayon::prelude::*;
+            indices.par_iter().map(compile).collect()
+        }
+
+        #[cfg(not(feature = "batch"))]
+        {
+            indices.iter().map(compile).collect()
+        }
+    }
+
+    fn compile_page(&self, path: &Path) -> Result<CompileResult, CompileError> {
+        let mut builder = TypstWorld::builder(path, &self.root)
+            .with_project_cache(self.cache.clone())
+            .with_fonts();
+
+        if let Some(inputs) = &self.inputs {
+            builder = builder.with_inputs_dict(inputs.clone());
+        }
+        if !self.preludes.is_empty() {
+            builder = builder.with_prelude(self.preludes.join("\n"));
+        }
+        if !self.postludes.is_empty() {
+            builder = builder.with_postlude(self.postludes.join("\n"));
+        }
+
+        compile_with_world(&self.timestamp.apply(self.interrupt.apply(builder)).build())
+    }
+}
+
+/// Outcome of [`Project::recompile`].
+#[derive(Debug, Default)]
+pub struct ProjectUpdate {
+    /// Pages that were compiled, with their results.
+    pub compiled: Vec<(PathBuf, Result<CompileResult, CompileError>)>,
+    /// Pages that were up to date and not compiled.
+    pub skipped: Vec<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_project_recompiles_only_affected_pages() {
+        let dir = TempDir::new().unwrap();
+        let lib = dir.path().join("lib.typ");
+        let a = dir.path().join("a.typ");
+        let b = dir.path().join("b.typ");
+        fs::write(&lib, r#"#let title = "Old""#).unwrap();
+        fs::write(&a, "#import \"lib.typ\": title\n= #title").unwrap();
+        fs::write(&b, "= Standalone").unwrap();
+
+        let mut project = Project::new(dir.path());
+        project.add_pages(&[&a, &b]);
+
+        let update = project.recompile();
+        assert_eq!(update.compiled.len(), 2);
+        assert!(update.skipped.is_empty());
+        assert!(project.deps(&a).unwrap().contains(&normalize_path(&lib)));
+
+        // Nothing changed
+        let update = project.recompile();
+        assert!(update.compiled.is_empty());
+        assert_eq!(update.skipped.len(), 2);
+
+        // Only `a` depends on `lib.typ`
+        fs::write(&lib, r#"#let title = "New""#).unwrap();
+        project.changed(&[&lib]);
+        let update = project.recompile();
+        assert_eq!(update.compiled.len(), 1);
+        assert_eq!(update.compiled[0].0, normalize_path(&a));
+        assert_eq!(update.skipped, [normalize_path(&b)]);
+
+        let html = update.compiled[0].1.as_ref().unwrap().html().unwrap();
+        assert!(String::from_utf8_lossy(&html).contains("New"));
+    }
+
+    #[test]
+    fn test_project_cache_is_not_reloaded_without_notification() {
+        let dir = TempDir::new().unwrap();
+        let lib = dir.path().join("lib.typ");
+        let a = dir.path().join("a.typ");
+        fs::write(&lib, r#"#let title = "Cached""#).unwrap();
+        fs::write(&a, "#import \"lib.typ\": title\n= #title").unwrap();
+
+        let mut project = Project::new(dir.path());
+        project.add_pages(&[&a]);
+        project.recompile();
+
+        // Edit without notifying `lib.typ`; only the page itself is marked
+        fs::write(&lib, r#"#let title = "Fresh""#).unwrap();
+        project.changed(&[&a]);
+        let update = project.recompile();
+        let html = update.compiled[0].1.as_ref().unwrap().html().unwrap();
+        assert!(String::from_utf8_lossy(&html).contains("Cached"));
+    }
+
+    #[test]
+    fn test_project_failed_page_stays_dirty() {
+        let dir = TempDir::new().unwrap();
+        let a = dir.path().join("a.typ");
+        fs::write(&a, "#import \"missing.typ\": x\n= #x").unwrap();
+
+        let mut project = Project::new(dir.path());
+        project.add_pages(&[&a]);
+        assert!(project.recompile().compiled[0].1.is_err());
+
+        // The missing file appears; the failed page is retried automatically
+        fs::write(dir.path().join("missing.typ"), "#let x = 1").unwrap();
+        let update = project.recompile();
+        assert!(update.compiled[0].1.is_ok());
+        assert!(project.recompile().compiled.is_empty());
+    }
+}
@@ -23,15 +23,22 @@
 
 use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use serde_json::Value as JsonValue;
 use typst::comemo::Track;
 use typst::diag::SourceDiagnostic;
 use typst::engine::{Route, Sink, Traced};
-use typst::foundations::{Content, Dict, Label};
+use rustc_hash::{FxHashMap, FxHashSet};
+use typst::foundations::{Content, Dict, Label, Repr, Smart};
+use typst::layout::Length;
 use typst::introspection::MetadataElem;
 use typst::loading::DataSource;
-use typst::model::{Destination, HeadingElem, LinkElem, LinkTarget};
+use typst::model::{
+    BibliographyElem, CiteElem, Destination, FigureElem, FigureKind, FootnoteBody, FootnoteElem,
+    HeadingElem, LinkElem, LinkTarget, RefElem, TableElem,
+};
+use typst::text::{RawContent, RawElem};
 use typst::utils::PicoStr;
 use typst::visualize::ImageElem;
 use typst::World;
@@ -39,10 +46,17 @@ use typst::ROUTINES;
 use typst_html::{HtmlAttr, HtmlElem};
 
 use super::inputs::WithInputs;
+use super::interrupt::{InterruptSettings, WithInterrupt};
+use super::timestamp::{TimestampSettings, WithTimestamp};
 use super::session::{AccessedDeps, CompileSession};
-use crate::diagnostic::{has_errors, CompileError};
+use super::stats::CompileStats;
+use super::syntax::{scan_syntax_impl, SyntaxScan};
+pub use super::extract::{DynExtractor, ExtractorSet, Filter, IntoJson, Map, Take, Within};
+use crate::codegen::{content_to_json, parse_selector, requires_layout, value_to_json};
+use crate::diagnostic::{has_errors, CompileError, SourceLocation, SpanResolver};
+use crate::html::{Outline, OutlineBuilder};
 use crate::resource::file::PackageId;
-use crate::world::TypstWorld;
+use crate::world::{join_in_root, LibraryStrategy, TypstWorld, WorldBuilder};
 
 /// Builder for fast Typst scanning (Eval-only, skips Layout).
 ///
@@ -53,6 +67,8 @@ use crate::world::TypstWorld;
 pub struct Scanner<'a> {
     root: &'a Path,
     inputs: Option<Dict>,
+    interrupt: InterruptSettings,
+    timestamp: TimestampSettings,
 }
 
 impl<'a> WithInputs for Scanner<'a> {
@@ -61,31 +77,85 @@ impl<'a> WithInputs for Scanner<'a> {
     }
 }
 
+impl<'a> WithInterrupt for Scanner<'a> {
+    fn interrupt_mut(&mut self) -> &mut InterruptSettings {
+        &mut self.interrupt
+    }
+}
+
+impl<'a> WithTimestamp for Scanner<'a> {
+    fn timestamp_mut(&mut self) -> &mut TimestampSettings {
+        &mut self.timestamp
+    }
+}
+
 impl<'a> Scanner<'a> {
     /// Create a new scanner with the given root directory.
     pub fn new(root: &'a Path) -> Self {
-        Self { root, inputs: None }
+        Self {
+            root,
+            inputs: None,
+            interrupt: InterruptSettings::default(),
+            timestamp: TimestampSettings::default(),
+        }
     }
 
     /// Execute the scan on a single file.
     pub fn scan<P: AsRef<Path>>(self, path: P) -> Result<ScanResult, CompileError> {
         let path = path.as_ref();
-        let world = self.build_world(path);
-        scan_impl(&world)
+        scan_impl(self.build_world(path))
+    }
+
+    /// Execute the scan on in-memory text as the main file.
+    ///
+    /// `name` is a path relative to the root, used for relative imports and
+    /// diagnostics. See [`Compiler::with_source`](super::compile::Compiler::with_source).
+    pub fn scan_source(self, name: impl AsRef<Path>, text: impl Into<String>) -> Result<ScanResult, CompileError> {
+        let path = join_in_root(self.root, name.as_ref())?;
+        let builder = self.world_builder(&path).with_main_source(text);
+        scan_impl(builder.build())
+    }
+
+    /// Read the file's syntax tree without evaluating it.
+    ///
+    /// Much cheaper than [`scan`](Self::scan): imports and packages are not
+    /// loaded. Only literal values written in the file are found; see
+    /// [`SyntaxScan`](super::syntax::SyntaxScan).
+    pub fn scan_syntax<P: AsRef<Path>>(self, path: P) -> Result<SyntaxScan, CompileError> {
+        scan_syntax_impl(&self.build_world(path.as_ref()))
+    }
+
+    /// Get labeled metadata, evaluating only when needed.
+    ///
+    /// Reads a literal `#metadata(...) <label>` from the syntax tree and
+    /// falls back to a full [`scan`](Self::scan) if the value is not a
+    /// literal, or if the label is not written in the file but code could
+    /// produce it (e.g. a template function; see
+    /// [`SyntaxScan::has_code`](super::syntax::SyntaxScan::has_code)).
+    pub fn scan_metadata<P: AsRef<Path>>(self, path: P, label: &str) -> Result<Option<JsonValue>, CompileError> {
+        let world = self.build_world(path.as_ref());
+        let syntax = scan_syntax_impl(&world)?;
+        match syntax.metadata_entry(label) {
+            Some(entry) if entry.value.is_some() => Ok(entry.value.clone()),
+            None if !syntax.has_code() => Ok(None),
+            _ => Ok(scan_impl(world)?.metadata(label)),
+        }
     }
 
     fn build_world(&self, path: &Path) -> TypstWorld {
-        match &self.inputs {
-            Some(inputs) => TypstWorld::builder(path, self.root)
-                .with_local_cache()
-                .no_fonts()
-                .with_inputs_dict(inputs.clone())
-                .build(),
-            None => TypstWorld::builder(path, self.root)
-                .with_local_cache()
-                .no_fonts()
-                .build(),
+        self.world_builder(path).build()
+    }
+
+    fn world_builder(&self, path: &Path) -> WorldBuilder {
+        let mut builder = TypstWorld::builder(path, self.root)
+            .with_local_cache()
+            .no_fonts();
+
+        if let Some(inputs) = &self.inputs {
+            builder = builder.with_inputs_dict(inputs.clone());
         }
+
+        self.timestamp.apply(self.interrupt.apply(builder))
     }
 }
 
@@ -98,6 +168,12 @@ pub struct ScanResult {
     accessed: AccessedDeps,
     /// Scan diagnostics (warnings only).
     diagnostics: Vec<SourceDiagnostic>,
+    /// Timing and file access statistics.
+    stats: CompileStats,
+    /// Resolves element spans to source locations.
+    resolver: SpanResolver,
+    /// Library the content was evaluated with, for selector queries.
+    library: LibraryStrategy,
 }
 
 impl ScanResult {
@@ -115,7 +191,8 @@ impl ScanResult {
     /// ));
     /// ```
     #[inline]
-    pub fn extract<E: Extractor>(&self, extractor: E) -> E::Output {
+    pub fn extract<E: Extractor>(&self, mut extractor: E) -> E::Output {
+        extractor.set_resolver(&self.resolver);
         extract(&self.content, extractor)
     }
 
@@ -137,11 +214,101 @@ impl ScanResult {
         MetadataExtractor::new(label).and_then(|e| self.extract(e))
     }
 
+    /// Build the heading outline with anchor ids.
+    ///
+    /// Uses the same id rules as [`HtmlDocument::outline`](crate::html::HtmlDocument::outline),
+    /// except that ids Typst would only assign during export are unknown.
+    pub fn outline(&self) -> Outline {
+        self.outline_with(OutlineBuilder::new())
+    }
+
+    /// Build the heading outline with a configured builder.
+    pub fn outline_with(&self, mut builder: OutlineBuilder) -> Outline {
+        let existing = FxHashMap::default();
+        let id_attr = HtmlAttr::intern("id").ok();
+        let _ = self.content.traverse(&mut |elem: Content| {
+            if elem.is::<HeadingElem>() {
+                builder.push_elem(&elem, &existing);
+                return ControlFlow::<()>::Continue(());
+            }
+            if let Some(label) = elem.label() {
+                builder.reserve(label.resolve().to_string());
+            }
+            if let Some(html_elem) = elem.to_packed::<HtmlElem>()
+                && let Some(id) = id_attr.and_then(|id| html_elem.attrs.get_cloned(Default::default()).get(id).cloned())
+            {
+                builder.reserve(id.to_string());
+            }
+            ControlFlow::Continue(())
+        });
+        builder.build()
+    }
+
+    /// Query elements with a Typst selector expression.
+    ///
+    /// Accepts the same selector strings as
+    /// [`HtmlDocument::query`](crate::html::HtmlDocument::query), evaluated
+    /// with the scan's library (including `sys.inputs`) and matched
+    /// against the Eval-only content tree. Without layout there is no
+    /// introspector, so:
+    /// - `before`/`after`, location and text/regex selectors return an error
+    /// - `where` only sees fields set explicitly, not resolved ones
+    ///   (e.g. `heading.where(depth: 2)` works, `heading.where(level: 2)` does not)
+    /// - counters, locations and numbering are not available
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let sections = result.query("heading.where(depth: 1)")?;
+    /// let items = result.query("<item>")?;
+    /// ```
+    pub fn query(&self, selector: &str) -> Result<Vec<JsonValue>, CompileError> {
+        Ok(self.query_content(selector)?.iter().map(content_to_json).collect())
+    }
+
+    /// Query elements and project a single field.
+    ///
+    /// Elements without the field are skipped. See [`query`](Self::query)
+    /// for the limitations without layout.
+    pub fn query_field(&self, selector: &str, field: &str) -> Result<Vec<JsonValue>, CompileError> {
+        Ok(self
+            .query_content(selector)?
+            .iter()
+            .filter_map(|elem| elem.get_by_name(field).ok())
+            .map(|value| value_to_json(&value))
+            .collect())
+    }
+
+    fn query_content(&self, selector: &str) -> Result<Vec<Content>, CompileError> {
+        let selector = parse_selector(selector, &self.library)?;
+        if let Some(kind) = requires_layout(&selector) {
+            return Err(CompileError::query(format!(
+                "{kind} selectors require layout and are not supported when scanning"
+            )));
+        }
+
+        let mut matches = Vec::new();
+        let _ = self.content.traverse(&mut |elem: Content| {
+            if selector.matches(&elem, None) {
+                matches.push(elem);
+            }
+            ControlFlow::<()>::Continue(())
+        });
+        Ok(matches)
+    }
+
     /// Get the raw content tree.
     pub fn content(&self) -> &Content {
         &self.content
     }
 
+    /// Get the resolver for element source locations.
+    ///
+    /// Useful for custom extractors working on [`content()`](Self::content).
+    pub fn resolver(&self) -> &SpanResolver {
+        &self.resolver
+    }
+
     /// Get files and packages accessed during scanning.
     pub fn accessed(&self) -> &AccessedDeps {
         &self.accessed
@@ -163,6 +330,17 @@ impl ScanResult {
     pub fn diagnostics(&self) -> &[SourceDiagnostic] {
         &self.diagnostics
     }
+
+    /// Get timing and file access statistics (Eval only; layout is zero).
+    pub fn stats(&self) -> CompileStats {
+        self.stats
+    }
+}
+
+impl AsRef<AccessedDeps> for ScanResult {
+    fn as_ref(&self) -> &AccessedDeps {
+        &self.accessed
+    }
 }
 
 /// Trait for extracting data from Typst Content.
@@ -177,6 +355,46 @@ pub trait Extractor: Sized {
 
     /// Finalize and return the extracted data.
     fn finish(self) -> Self::Output;
+
+    /// Receive the span resolver before traversal.
+    ///
+    /// Called by [`ScanResult::extract`]. Extractors that report source
+    /// locations keep a clone; the default ignores it.
+    fn set_resolver(&mut self, resolver: &SpanResolver) {
+        let _ = resolver;
+    }
+
+    /// Keep only the items matching `predicate`.
+    fn filter<T, P>(self, predicate: P) -> Filter<Self, P>
+    where
+        Self: Extractor<Output = Vec<T>>,
+        P: FnMut(&T) -> bool,
+    {
+        Filter::new(self, predicate)
+    }
+
+    /// Transform the output with `f`.
+    fn map<U, F>(self, f: F) -> Map<Self, F>
+    where
+        F: FnOnce(Self::Output) -> U,
+    {
+        Map::new(self, f)
+    }
+
+    /// Keep at most the first `n` items.
+    fn take<T>(self, n: usize) -> Take<Self>
+    where
+        Self: Extractor<Output = Vec<T>>,
+    {
+        Take::new(self, n)
+    }
+
+    /// Only visit elements inside elements labeled `label`.
+    ///
+    /// Returns `None` if the label is empty.
+    fn within(self, label: &str) -> Option<Within<Self>> {
+        Within::new(self, label)
+    }
 }
 
 /// Extract data from Content using an extractor.
@@ -203,6 +421,13 @@ macro_rules! impl_extractor_for_tuple {
                 let ($first, $($rest,)*) = self;
                 ($first.finish(), $($rest.finish(),)*)
             }
+
+            #[allow(non_snake_case)]
+            fn set_resolver(&mut self, resolver: &SpanResolver) {
+                let ($first, $($rest,)*) = self;
+                $first.set_resolver(resolver);
+                $($rest.set_resolver(resolver);)*
+            }
         }
 
         impl_extractor_for_tuple!($($rest),*);
@@ -218,6 +443,7 @@ pub struct LinkExtractor {
     links: Vec<Link>,
     href_attr: Option<HtmlAttr>,
     src_attr: Option<HtmlAttr>,
+    resolver: SpanResolver,
 }
 
 impl LinkExtractor {
@@ -227,6 +453,7 @@ impl LinkExtractor {
             links: Vec::new(),
             href_attr: HtmlAttr::intern("href").ok(),
             src_attr: HtmlAttr::intern("src").ok(),
+            resolver: SpanResolver::default(),
         }
     }
 }
@@ -235,12 +462,15 @@ impl Extractor for LinkExtractor {
     type Output = Vec<Link>;
 
     fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        let location = || self.resolver.resolve(elem.span());
+
         if let Some(link) = elem.to_packed::<LinkElem>()
             && let LinkTarget::Dest(Destination::Url(url)) = &link.dest
         {
             self.links.push(Link {
                 dest: url.as_str().to_string(),
                 source: LinkSource::Link,
+                location: location(),
             });
         }
 
@@ -253,6 +483,7 @@ impl Extractor for LinkExtractor {
                 self.links.push(Link {
                     dest: value.to_string(),
                     source: LinkSource::Href,
+                    location: location(),
                 });
             }
 
@@ -262,18 +493,20 @@ impl Extractor for LinkExtractor {
                 self.links.push(Link {
                     dest: value.to_string(),
                     source: LinkSource::Src,
+                    location: location(),
                 });
             }
         }
 
         // Extract image source paths
-        if let Some(image) = elem.to_packed::<ImageElem>() {
-            if let DataSource::Path(path) = &image.source.source {
-                self.links.push(Link {
-                    dest: path.to_string(),
-                    source: LinkSource::Image,
-                });
-            }
+        if let Some(image) = elem.to_packed::<ImageElem>()
+            && let DataSource::Path(path) = &image.source.source
+        {
+            self.links.push(Link {
+                dest: path.to_string(),
+                source: LinkSource::Image,
+                location: location(),
+            });
         }
 
         ControlFlow::Continue(())
@@ -282,6 +515,10 @@ impl Extractor for LinkExtractor {
     fn finish(self) -> Self::Output {
         self.links
     }
+
+    fn set_resolver(&mut self, resolver: &SpanResolver) {
+        self.resolver = resolver.clone();
+    }
 }
 
 /// A link extracted from the document.
@@ -291,6 +528,10 @@ pub struct Link {
     pub dest: String,
     /// Where this link came from.
     pub source: LinkSource,
+    /// Where the link is written in the source.
+    ///
+    /// Only available through [`ScanResult::extract`] and friends.
+    pub location: Option<SourceLocation>,
 }
 
 /// The source of a link.
@@ -344,6 +585,7 @@ impl Link {
 #[derive(Debug, Default)]
 pub struct HeadingExtractor {
     headings: Vec<Heading>,
+    resolver: SpanResolver,
 }
 
 impl HeadingExtractor {
@@ -371,7 +613,8 @@ impl Extractor for HeadingExtractor {
                     typst::model::Supplement::Func(_) => None,
                 })
                 .filter(|s| s != "Section");
-            self.headings.push(Heading { level, text, supplement });
+            let location = self.resolver.resolve(elem.span());
+            self.headings.push(Heading { level, text, supplement, location });
         }
         ControlFlow::Continue(())
     }
@@ -379,6 +622,10 @@ impl Extractor for HeadingExtractor {
     fn finish(self) -> Self::Output {
         self.headings
     }
+
+    fn set_resolver(&mut self, resolver: &SpanResolver) {
+        self.resolver = resolver.clone();
+    }
 }
 
 /// A heading extracted from the document.
@@ -391,6 +638,10 @@ pub struct Heading {
     /// Heading supplement (e.g., "Section", "Chapter").
     /// Used for custom heading IDs when not default.
     pub supplement: Option<String>,
+    /// Where the heading is written in the source.
+    ///
+    /// Only available through [`ScanResult::extract`] and friends.
+    pub location: Option<SourceLocation>,
 }
 
 /// Extracts metadata by label.
@@ -432,8 +683,394 @@ impl Extractor for MetadataExtractor {
     }
 }
 
+/// Extracts all figures from the document.
+#[derive(Debug, Default)]
+pub struct FigureExtractor {
+    figures: Vec<Figure>,
+}
+
+impl FigureExtractor {
+    /// Create a new figure extractor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Extractor for FigureExtractor {
+    type Output = Vec<Figure>;
+
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        if let Some(figure) = elem.to_packed::<FigureElem>() {
+            let kind = match figure.kind.get_cloned(Default::default()) {
+                Smart::Custom(FigureKind::Elem(elem)) => Some(elem.name().to_string()),
+                Smart::Custom(FigureKind::Name(name)) => Some(name.to_string()),
+                Smart::Auto => infer_figure_kind(&figure.body),
+            };
+            let caption = figure
+                .caption
+                .get_cloned(Default::default())
+                .map(|caption| caption.body.plain_text().to_string());
+            self.figures.push(Figure {
+                kind,
+                caption,
+                alt: figure.alt.get_cloned(Default::default()).map(Into::into),
+                label: label_name(elem),
+            });
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn finish(self) -> Self::Output {
+        self.figures
+    }
+}
+
+/// Infer the kind of an `auto` figure from its body, like Typst does during layout.
+fn infer_figure_kind(body: &Content) -> Option<String> {
+    let mut kind = None;
+    let _ = body.traverse(&mut |elem: Content| {
+        if elem.is::<ImageElem>() || elem.is::<TableElem>() || elem.is::<RawElem>() {
+            kind = Some(elem.elem().name().to_string());
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    });
+    kind
+}
+
+/// A figure extracted from the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Figure {
+    /// Figure kind (`image`, `table`, `raw` or a custom name).
+    /// `None` if `auto` and the body contains none of the built-in kinds.
+    pub kind: Option<String>,
+    /// Caption text (plain text).
+    pub caption: Option<String>,
+    /// Alternative description of the figure.
+    pub alt: Option<String>,
+    /// Label attached to the figure, without angle brackets.
+    pub label: Option<String>,
+}
+
+/// Extracts all images from the document.
+#[derive(Debug, Default)]
+pub struct ImageExtractor {
+    images: Vec<Image>,
+}
+
+impl ImageExtractor {
+    /// Create a new image extractor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Extractor for ImageExtractor {
+    type Output = Vec<Image>;
+
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        if let Some(image) = elem.to_packed::<ImageElem>() {
+            let path = match &image.source.source {
+                DataSource::Path(path) => Some(path.to_string()),
+                DataSource::Bytes(_) => None,
+            };
+            let width = match image.width.get(Default::default()) {
+                // `Rel::repr` always prints both parts (`50% + 0pt`)
+                Smart::Custom(width) if width.abs == Length::zero() => Some(width.rel.repr().to_string()),
+                Smart::Custom(width) if width.rel.is_zero() => Some(width.abs.repr().to_string()),
+                Smart::Custom(width) => Some(width.repr().to_string()),
+                Smart::Auto => None,
+            };
+            self.images.push(Image {
+                path,
+                alt: image.alt.get_cloned(Default::default()).map(Into::into),
+                width,
+            });
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn finish(self) -> Self::Output {
+        self.images
+    }
+}
+
+/// An image extracted from the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    /// Source path as written (`None` for images loaded from bytes).
+    pub path: Option<String>,
+    /// Alternative text (`None` if missing, useful for accessibility audits).
+    pub alt: Option<String>,
+    /// Width as written in Typst (e.g., `50%`, `10pt`), `None` if `auto`.
+    pub width: Option<String>,
+}
+
+/// Extracts all raw text (code) elements from the document.
+#[derive(Debug, Default)]
+pub struct RawExtractor {
+    raws: Vec<Raw>,
+}
+
+impl RawExtractor {
+    /// Create a new raw extractor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Extractor for RawExtractor {
+    type Output = Vec<Raw>;
+
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        if let Some(raw) = elem.to_packed::<RawElem>() {
+            let text = match &raw.text {
+                RawContent::Text(text) => text.to_string(),
+                RawContent::Lines(lines) => {
+                    lines.iter().map(|(line, _)| line.as_str()).collect::<Vec<_>>().join("\n")
+                }
+            };
+            self.raws.push(Raw {
+                lang: raw.lang.get_cloned(Default::default()).map(Into::into),
+                text,
+                block: raw.block.get(Default::default()),
+            });
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn finish(self) -> Self::Output {
+        self.raws
+    }
+}
+
+/// A raw text (code) element extracted from the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Raw {
+    /// Language tag (e.g., `rust`).
+    pub lang: Option<String>,
+    /// The raw text.
+    pub text: String,
+    /// Whether this is a block (```` ``` ````) rather than inline code.
+    pub block: bool,
+}
+
+/// Extracts all footnotes from the document.
+#[derive(Debug, Default)]
+pub struct FootnoteExtractor {
+    footnotes: Vec<Footnote>,
+}
+
+impl FootnoteExtractor {
+    /// Create a new footnote extractor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Extractor for FootnoteExtractor {
+    type Output = Vec<Footnote>;
+
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        if let Some(footnote) = elem.to_packed::<FootnoteElem>() {
+            self.footnotes.push(match &footnote.body {
+                FootnoteBody::Content(body) => Footnote {
+                    text: body.plain_text().to_string(),
+                    reference: None,
+                },
+                FootnoteBody::Reference(label) => Footnote {
+                    text: String::new(),
+                    reference: Some(label.resolve().to_string()),
+                },
+            });
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn finish(self) -> Self::Output {
+        self.footnotes
+    }
+}
+
+/// A footnote extracted from the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footnote {
+    /// Footnote text (plain text, empty for references).
+    pub text: String,
+    /// Label of the referenced footnote for `footnote(<label>)`.
+    pub reference: Option<String>,
+}
+
+/// Extracts citation keys from the document.
+///
+/// `@key` syntax produces a reference, which Typst turns into a citation
+/// during layout if no element in the document has that label. The
+/// extractor applies the same rule, so unresolved `@key` references are
+/// reported as [`CitationSource::Ref`].
+#[derive(Debug, Default)]
+pub struct CitationExtractor {
+    citations: Vec<Citation>,
+    labels: FxHashSet<Label>,
+    refs: Vec<(usize, Label)>,
+}
+
+impl CitationExtractor {
+    /// Create a new citation extractor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Extractor for CitationExtractor {
+    type Output = Vec<Citation>;
+
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        if let Some(label) = elem.label() {
+            self.labels.insert(label);
+        }
+
+        if let Some(cite) = elem.to_packed::<CiteElem>() {
+            self.citations.push(Citation {
+                key: cite.key.resolve().to_string(),
+                source: CitationSource::Cite,
+            });
+        } else if let Some(reference) = elem.to_packed::<RefElem>() {
+            // Resolved in `finish()`, once all labels are known
+            self.refs.push((self.citations.len(), reference.target));
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn finish(self) -> Self::Output {
+        let mut citations = self.citations;
+        // Insert in reverse so earlier indices stay valid
+        for (index, target) in self.refs.into_iter().rev() {
+            if !self.labels.contains(&target) {
+                citations.insert(index, Citation {
+                    key: target.resolve().to_string(),
+                    source: CitationSource::Ref,
+                });
+            }
+        }
+        citations
+    }
+}
+
+/// A citation extracted from the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Citation {
+    /// Bibliography key.
+    pub key: String,
+    /// Where this citation came from.
+    pub source: CitationSource,
+}
+
+/// The source of a citation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CitationSource {
+    /// From `#cite()` element.
+    Cite,
+    /// From `@key` reference without a matching label in the document.
+    Ref,
+}
+
+/// Extracts bibliography declarations from the document.
+#[derive(Debug, Default)]
+pub struct BibliographyExtractor {
+    bibliographies: Vec<Bibliography>,
+}
+
+impl BibliographyExtractor {
+    /// Create a new bibliography extractor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Extractor for BibliographyExtractor {
+    type Output = Vec<Bibliography>;
+
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        if let Some(bibliography) = elem.to_packed::<BibliographyElem>() {
+            let sources = bibliography
+                .sources
+                .source
+                .0
+                .iter()
+                .filter_map(|source| match source {
+                    DataSource::Path(path) => Some(path.to_string()),
+                    DataSource::Bytes(_) => None,
+                })
+                .collect();
+            self.bibliographies.push(Bibliography { sources });
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn finish(self) -> Self::Output {
+        self.bibliographies
+    }
+}
+
+/// A bibliography declaration extracted from the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bibliography {
+    /// Source file paths as written (sources loaded from bytes are omitted).
+    pub sources: Vec<String>,
+}
+
+/// Extracts all labeled elements from the document.
+#[derive(Debug, Default)]
+pub struct LabelExtractor {
+    labels: Vec<Labeled>,
+}
+
+impl LabelExtractor {
+    /// Create a new label extractor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Extractor for LabelExtractor {
+    type Output = Vec<Labeled>;
+
+    fn visit(&mut self, elem: &Content) -> ControlFlow<()> {
+        if let Some(label) = label_name(elem) {
+            self.labels.push(Labeled {
+                label,
+                kind: elem.elem().name().to_string(),
+                text: elem.plain_text().to_string(),
+            });
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn finish(self) -> Self::Output {
+        self.labels
+    }
+}
+
+/// A labeled element extracted from the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Labeled {
+    /// Label name, without angle brackets.
+    pub label: String,
+    /// Element name (e.g., `heading`, `figure`, `metadata`).
+    pub kind: String,
+    /// Plain text of the element.
+    pub text: String,
+}
+
+#[inline]
+fn label_name(elem: &Content) -> Option<String> {
+    elem.label().map(|label| label.resolve().to_string())
+}
+
 /// Internal scan implementation, exposed for BatchCompiler reuse.
-pub(crate) fn scan_impl(world: &TypstWorld) -> Result<ScanResult, CompileError> {
+pub(crate) fn scan_impl(world: TypstWorld) -> Result<ScanResult, CompileError> {
+    world.check_interrupt()?;
+    let start = Instant::now();
     let session = CompileSession::start();
     let line_offset = world.prelude_line_count();
 
@@ -444,7 +1081,7 @@ pub(crate) fn scan_impl(world: &TypstWorld) -> Result<ScanResult, CompileError>
         .source(world.main())
         .map_err(|e| CompileError::html_export(format!("Failed to read source: {e:?}")))?;
 
-    let world_ref: &dyn World = world;
+    let world_ref: &dyn World = &world;
     let result = typst_eval::eval(
         &ROUTINES,
         world_ref.track(),
@@ -454,23 +1091,39 @@ pub(crate) fn scan_impl(world: &TypstWorld) -> Result<ScanResult, CompileError>
         &source,
     );
 
+    let eval = start.elapsed();
     let warnings = sink.warnings();
+    if result.is_err() || has_errors(&warnings) {
+        // Errors may stem from file accesses refused by the interrupt
+        world.check_interrupt()?;
+    }
 
     let module = result.map_err(|errors| {
         let all_diags: Vec<_> = errors.iter().chain(&warnings).cloned().collect();
-        CompileError::compilation_with_offset(world, all_diags, line_offset)
+        CompileError::compilation_with_offset(&world, all_diags, line_offset)
     })?;
 
     if has_errors(&warnings) {
-        return Err(CompileError::compilation_with_offset(world, warnings.to_vec(), line_offset));
+        return Err(CompileError::compilation_with_offset(&world, warnings.to_vec(), line_offset));
     }
 
     let accessed = session.finish(world.root());
+    // Read before the resolver loads any sources
+    let stats = CompileStats {
+        eval,
+        total: start.elapsed(),
+        files: world.file_stats(),
+        ..Default::default()
+    };
+    let library = world.library_strategy().clone();
 
     Ok(ScanResult {
         content: module.content(),
         accessed,
         diagnostics: warnings.to_vec(),
+        stats,
+        resolver: SpanResolver::from_world(world.without_interrupt(), line_offset),
+        library,
     })
 }
 
@@ -510,6 +1163,54 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_scanner_cancelled() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("test.typ");
+        fs::write(&file, "= Hello").unwrap();
+
+        let token = crate::world::CancellationToken::new();
+        token.cancel();
+
+        let result = Scanner::new(dir.path()).with_cancellation(token).scan(&file);
+        assert!(matches!(result, Err(CompileError::Cancelled)));
+    }
+
+    #[test]
+    fn test_scanner_cpu_loop_timeout() {
+        use std::time::Duration;
+
+        const LOOP: &str = "#let n = 0\n#for i in range(20000) { n += 2 * i }\n= #n";
+        let dir = TempDir::new().unwrap();
+        let result = Scanner::new(dir.path())
+            .with_timeout(Duration::ZERO)
+            .scan_source("loop.typ", LOOP);
+        assert!(matches!(result, Err(CompileError::TimedOut { .. })));
+
+        // The loop touches no files, so nothing interrupts it midway
+        let result = Scanner::new(dir.path())
+            .with_timeout(Duration::from_secs(3600))
+            .scan_source("loop.typ", LOOP)
+            .unwrap();
+        assert_eq!(result.headings()[0].text, "399980000");
+    }
+
+    #[test]
+    fn test_scan_source() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("lib.typ"), r#"#let url = "https://example.com""#).unwrap();
+
+        let result = Scanner::new(dir.path())
+            .scan_source("input.typ", "#import \"lib.typ\": url\n#link(url)[Example]")
+            .unwrap();
+
+        assert_eq!(result.links().len(), 1);
+        assert_eq!(result.links()[0].dest, "https://example.com");
+
+        let err = Scanner::new(dir.path()).scan_source("../input.typ", "= X").unwrap_err();
+        assert!(matches!(err, CompileError::Io(_)));
+    }
+
     #[test]
     fn test_extract_links() {
         let dir = TempDir::new().unwrap();
@@ -537,7 +1238,7 @@ mod tests {
 
         // Create a dummy image file
         let img_path = dir.path().join("test.png");
-        fs::write(&img_path, &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]).unwrap();
+        fs::write(&img_path, [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]).unwrap();
 
         let file = dir.path().join("test.typ");
         fs::write(
@@ -594,6 +1295,106 @@ mod tests {
         assert!(meta.is_some());
     }
 
+    #[test]
+    fn test_extract_figures_images_raw() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("logo.svg"),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1"></svg>"#,
+        )
+        .unwrap();
+
+        let result = Scanner::new(dir.path())
+            .scan_source(
+                "test.typ",
+                r#"
+#figure(image("logo.svg", alt: "Logo", width: 50%), caption: [The logo]) <fig-logo>
+#figure(```rust
+fn main() {}
+```)
+#image("logo.svg")
+Inline `code` here.
+"#,
+            )
+            .unwrap();
+
+        let (figures, images, raws) = result.extract((
+            FigureExtractor::new(),
+            ImageExtractor::new(),
+            RawExtractor::new(),
+        ));
+
+        assert_eq!(figures.len(), 2);
+        assert_eq!(figures[0].kind.as_deref(), Some("image"));
+        assert_eq!(figures[0].caption.as_deref(), Some("The logo"));
+        assert_eq!(figures[0].label.as_deref(), Some("fig-logo"));
+        assert_eq!(figures[1].kind.as_deref(), Some("raw"));
+
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].path.as_deref(), Some("logo.svg"));
+        assert_eq!(images[0].alt.as_deref(), Some("Logo"));
+        assert_eq!(images[0].width.as_deref(), Some("50%"));
+        assert_eq!(images[1].alt, None);
+
+        assert_eq!(raws.len(), 2);
+        assert_eq!(raws[0].lang.as_deref(), Some("rust"));
+        assert_eq!(raws[0].text, "fn main() {}");
+        assert!(raws[0].block);
+        assert_eq!((raws[1].text.as_str(), raws[1].block), ("code", false));
+    }
+
+    #[test]
+    fn test_extract_footnotes_and_labels() {
+        let dir = TempDir::new().unwrap();
+        let result = Scanner::new(dir.path())
+            .scan_source(
+                "test.typ",
+                "= Intro <intro>\nText#footnote[A note] <note> again#footnote(<note>)",
+            )
+            .unwrap();
+
+        let (footnotes, labels) = result.extract((FootnoteExtractor::new(), LabelExtractor::new()));
+
+        assert_eq!(footnotes.len(), 2);
+        assert_eq!(footnotes[0].text, "A note");
+        assert_eq!(footnotes[1].reference.as_deref(), Some("note"));
+
+        let names: Vec<_> = labels.iter().map(|l| (l.label.as_str(), l.kind.as_str())).collect();
+        assert_eq!(names, [("intro", "heading"), ("note", "footnote")]);
+        assert_eq!(labels[0].text, "Intro");
+    }
+
+    #[test]
+    fn test_extract_citations() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("refs.bib"),
+            "@book{knuth, title = {TAOCP}, author = {Knuth, Donald}, year = {1968}}\n\
+             @book{lamport, title = {LaTeX}, author = {Lamport, Leslie}, year = {1986}}",
+        )
+        .unwrap();
+
+        let result = Scanner::new(dir.path())
+            .scan_source(
+                "test.typ",
+                "= Intro <intro>\nSee @intro, @knuth and #cite(<lamport>).\n#bibliography(\"refs.bib\")",
+            )
+            .unwrap();
+
+        let (citations, bibliographies) =
+            result.extract((CitationExtractor::new(), BibliographyExtractor::new()));
+
+        // `@intro` targets a label in the document, so it is not a citation
+        assert_eq!(
+            citations,
+            [
+                Citation { key: "knuth".into(), source: CitationSource::Ref },
+                Citation { key: "lamport".into(), source: CitationSource::Cite },
+            ]
+        );
+        assert_eq!(bibliographies, [Bibliography { sources: vec!["refs.bib".into()] }]);
+    }
+
     #[test]
     fn test_extract_tuple() {
         let dir = TempDir::new().unwrap();
@@ -617,14 +1418,137 @@ mod tests {
         assert_eq!(headings.len(), 1);
     }
 
+    #[test]
+    fn test_query() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("test.typ");
+        fs::write(
+            &file,
+            r#"
+= Intro
+== Details <details>
+#metadata((id: 1)) <item>
+#metadata((id: 2)) <item>
+"#,
+        )
+        .unwrap();
+
+        let result = Scanner::new(dir.path()).scan(&file).unwrap();
+
+        let sections = result.query("heading.where(depth: 2)").unwrap();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0]["label"], "<details>");
+
+        let ids = result.query_field("<item>", "value").unwrap();
+        assert_eq!(ids, [serde_json::json!({"id": 1}), serde_json::json!({"id": 2})]);
+
+        let either = result.query("selector(heading).or(<item>)").unwrap();
+        assert_eq!(either.len(), 4);
+
+        // The same selector string works on the compiled document
+        let compiled = crate::process::compile::Compiler::new(dir.path())
+            .with_path(&file)
+            .compile()
+            .unwrap();
+        assert_eq!(compiled.document().query_field("<item>", "value").unwrap(), ids);
+
+        // Selectors see the scan's `sys.inputs`
+        let result = Scanner::new(dir.path())
+            .with_inputs([("depth", "2")])
+            .scan(&file)
+            .unwrap();
+        let sections = result.query("heading.where(depth: int(sys.inputs.depth))").unwrap();
+        assert_eq!(sections.len(), 1);
+    }
+
+    #[test]
+    fn test_query_requires_layout() {
+        let dir = TempDir::new().unwrap();
+        let result = Scanner::new(dir.path()).scan_source("test.typ", "= Intro <intro>").unwrap();
+
+        let err = result.query("selector(heading).after(<intro>)").unwrap_err();
+        assert!(matches!(err, CompileError::Query { .. }));
+        assert!(result.query("heading.where(").is_err());
+    }
+
+    #[test]
+    fn test_extract_locations() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("lib.typ"), "#let footer = link(\"https://lib.com\")[Lib]").unwrap();
+        let file = dir.path().join("test.typ");
+        fs::write(&file, "#import \"lib.typ\": footer\n= Title\n\nSee #link(\"https://a.com\")[A]\n#footer").unwrap();
+
+        // The prelude shifts the main file; locations must not include it
+        let world = TypstWorld::builder(&file, dir.path())
+            .with_prelude("#let unused = 1\n#let also-unused = 2")
+            .with_local_cache()
+            .no_fonts()
+            .build();
+        let result = scan_impl(world).unwrap();
+        let (links, headings) = result.extract((LinkExtractor::new(), HeadingExtractor::new()));
+
+        let location = headings[0].location.as_ref().unwrap();
+        assert_eq!((location.path.as_str(), location.line, location.column), ("test.typ", 2, 1));
+
+        let location = links[0].location.as_ref().unwrap();
+        assert_eq!(location.to_string(), "test.typ:4:6");
+        let location = links[1].location.as_ref().unwrap();
+        assert_eq!(location.to_string(), "lib.typ:1:15");
+
+        // The free function has no resolver
+        assert!(extract(result.content(), LinkExtractor::new())[0].location.is_none());
+    }
+
+    #[test]
+    fn test_locations_resolved_lazily() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("lazy-lib.typ"), "#let more = link(\"https://lib.com\")[Lib]").unwrap();
+        let file = dir.path().join("lazy.typ");
+        fs::write(&file, "#import \"lazy-lib.typ\": more\n= Lazy\n#more").unwrap();
+
+        let token = crate::world::CancellationToken::new();
+        let result = Scanner::new(dir.path()).with_cancellation(token.clone()).scan(&file).unwrap();
+        // Both files are read once by eval; locations are not resolved yet
+        let files = result.stats().files;
+        assert_eq!((files.files_read, files.cache_hits), (2, 0));
+
+        // Cancelling after the scan does not affect locations
+        token.cancel();
+        let links = result.links();
+        assert_eq!(links[0].location.as_ref().unwrap().to_string(), "lazy-lib.typ:1:13");
+        assert_eq!(result.stats().files, files);
+    }
+
+    #[test]
+    fn test_outline_matches_document() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("test.typ");
+        fs::write(
+            &file,
+            "= Guide\n== Setup\n== Setup\n= Reference <ref>\n=== API\n#for t in (\"Use\", \"Use\") [== #t]\n[x] <use>",
+        )
+        .unwrap();
+
+        let scanned = Scanner::new(dir.path()).scan(&file).unwrap().outline();
+        let ids: Vec<_> = scanned.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["guide", "setup", "setup-1", "ref", "api", "use-1", "use-2"]);
+
+        let compiled = crate::process::compile::Compiler::new(dir.path())
+            .with_path(&file)
+            .compile()
+            .unwrap();
+        assert_eq!(compiled.document().outline().entries(), scanned.entries());
+    }
+
     #[test]
     fn test_link_classification() {
-        let http = Link { dest: "http://x.com".into(), source: LinkSource::Link };
-        let https = Link { dest: "https://x.com".into(), source: LinkSource::Link };
-        let mailto = Link { dest: "mailto:a@b.com".into(), source: LinkSource::Href };
-        let root = Link { dest: "/about".into(), source: LinkSource::Link };
-        let fragment = Link { dest: "#section".into(), source: LinkSource::Link };
-        let relative = Link { dest: "./img.png".into(), source: LinkSource::Src };
+        let link = |dest: &str, source| Link { dest: dest.into(), source, location: None };
+        let http = link("http://x.com", LinkSource::Link);
+        let https = link("https://x.com", LinkSource::Link);
+        let mailto = link("mailto:a@b.com", LinkSource::Href);
+        let root = link("/about", LinkSource::Link);
+        let fragment = link("#section", LinkSource::Link);
+        let relative = link("./img.png", LinkSource::Src);
 
         assert!(http.is_http() && http.is_external());
         assert!(https.is_http() && https.is_external());
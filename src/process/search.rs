@@ -0,0 +1,604 @@
+//! Full-text search index for client-side search.
+//!
+//! [`SearchIndex`] collects pages from compile or scan results, splits each
+//! page into sections at outlined headings (anchors match
+//! [`Outline`](crate::html::Outline)), and emits a compact JSON index.
+//!
+//! # Format
+//!
+//! ```json
+//! {
+//!   "documents": [{ "path": "posts/hello.typ", "title": "Hello" }],
+//!   "sections": [[0, "getting-started", "Getting Started", "First words of the section…"]],
+//!   "terms": { "install": [0, 2, 5, 1] }
+//! }
+//! ```
+//!
+//! - `sections[i]` is `[document, anchor, heading, excerpt]`; the part before
+//!   the first heading has a `null` anchor and an empty heading
+//! - `terms[t]` is a flat list of `section, count` pairs, sorted by section
+//!
+//! Terms are lowercased runs of alphanumeric characters (no stemming).
+//! Documents are sorted by path, so the output is deterministic.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let results = batcher.batch_compile(&pages)?;
+//!
+//! let mut index = SearchIndex::new(root);
+//! index.insert_results(&pages, &results);
+//! std::fs::write("public/search.json", index.to_json_string())?;
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value as JsonValue};
+use typst::layout::{Frame, FrameItem};
+
+use crate::html::{is_heading, HeadingKeys, HtmlDocument, HtmlElement, HtmlFrame, NodeKind, Outline};
+use crate::world::normalize_path;
+
+use super::compile::CompileResult;
+
+/// What text goes into the index.
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    /// Index code (`raw` elements, `<pre>`/`<code>`). Default: `false`.
+    pub include_code: bool,
+    /// Index text inside frames and equations. Default: `false`.
+    pub include_frames: bool,
+    /// Ignore terms shorter than this many characters. Default: `2`.
+    pub min_term_len: usize,
+    /// Maximum excerpt length in characters (`0` for no excerpts). Default: `160`.
+    pub excerpt_len: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            include_code: false,
+            include_frames: false,
+            min_term_len: 2,
+            excerpt_len: 160,
+        }
+    }
+}
+
+impl SearchOptions {
+    /// Create options with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether to index code.
+    pub fn with_code(mut self, include: bool) -> Self {
+        self.include_code = include;
+        self
+    }
+
+    /// Set whether to index text inside frames and equations.
+    pub fn with_frames(mut self, include: bool) -> Self {
+        self.include_frames = include;
+        self
+    }
+
+    /// Set the minimum term length.
+    pub fn with_min_term_len(mut self, len: usize) -> Self {
+        self.min_term_len = len;
+        self
+    }
+
+    /// Set the maximum excerpt length.
+    pub fn with_excerpt_len(mut self, len: usize) -> Self {
+        self.excerpt_len = len;
+        self
+    }
+}
+
+/// A page split into searchable sections.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchPage {
+    /// Page title (document title, else first heading).
+    pub title: Option<String>,
+    /// Sections in document order.
+    pub sections: Vec<SearchSection>,
+}
+
+/// A section of a page, from one heading to the next.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchSection {
+    /// Heading text (empty for the part before the first heading).
+    pub heading: String,
+    /// Anchor id of the heading, as assigned by [`Outline`].
+    pub anchor: Option<String>,
+    /// Plain text of the section body.
+    pub text: String,
+}
+
+/// Sources that can be split into a [`SearchPage`].
+pub trait Searchable {
+    /// Extract the page's sections.
+    fn search_page(&self, options: &SearchOptions) -> SearchPage;
+}
+
+impl Searchable for HtmlDocument {
+    fn search_page(&self, options: &SearchOptions) -> SearchPage {
+        let outline = self.outline();
+        let mut collector = Collector::new(options);
+        collector.html(self.root(), &outline);
+        let info_title = self.as_inner().info.title.as_ref().map(|t| t.to_string());
+        collector.finish(info_title)
+    }
+}
+
+impl Searchable for CompileResult {
+    fn search_page(&self, options: &SearchOptions) -> SearchPage {
+        self.document().search_page(options)
+    }
+}
+
+#[cfg(feature = "scan")]
+impl Searchable for super::scan::ScanResult {
+    fn search_page(&self, options: &SearchOptions) -> SearchPage {
+        let outline = self.outline();
+        let mut collector = Collector::new(options);
+        collector.content(self.content(), &outline);
+        collector.finish(None)
+    }
+}
+
+/// Search index over many pages.
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    root: PathBuf,
+    options: SearchOptions,
+    /// Root-relative path (with `/`) → page.
+    pages: BTreeMap<String, SearchPage>,
+}
+
+impl SearchIndex {
+    /// Create an empty index for the given project root.
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: normalize_path(root.as_ref()),
+            ..Self::default()
+        }
+    }
+
+    /// Set what text goes into the index.
+    pub fn with_options(mut self, options: SearchOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Add a page, replacing any previous entry for the same path.
+    pub fn insert(&mut self, page: impl AsRef<Path>, source: &impl Searchable) {
+        let page_path = self.relative(page.as_ref());
+        self.insert_page(page_path, source.search_page(&self.options));
+    }
+
+    /// Add an already extracted page under the given path.
+    pub fn insert_page(&mut self, path: impl Into<String>, page: SearchPage) {
+        self.pages.insert(path.into(), page);
+    }
+
+    /// Add successful batch results.
+    ///
+    /// `paths` and `results` must be in the same order, as returned by
+    /// `batch_compile()` / `batch_scan()`. Failed results are skipped.
+    pub fn insert_results<P, T, E>(&mut self, paths: &[P], results: &[Result<T, E>])
+    where
+        P: AsRef<Path>,
+        T: Searchable,
+    {
+        for (path, result) in paths.iter().zip(results) {
+            if let Ok(result) = result {
+                self.insert(path, result);
+            }
+        }
+    }
+
+    /// Remove a page. Returns `true` if it was present.
+    pub fn remove(&mut self, page: impl AsRef<Path>) -> bool {
+        let page_path = self.relative(page.as_ref());
+        self.pages.remove(&page_path).is_some()
+    }
+
+    /// Get the indexed pages by root-relative path.
+    pub fn pages(&self) -> impl Iterator<Item = (&str, &SearchPage)> {
+        self.pages.iter().map(|(path, page)| (path.as_str(), page))
+    }
+
+    /// Number of indexed pages.
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Check if no pages are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// Build the JSON index (see the [module docs](self) for the format).
+    pub fn to_json(&self) -> JsonValue {
+        let mut documents = Vec::with_capacity(self.pages.len());
+        let mut sections = Vec::new();
+        let mut terms: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+
+        for (doc, (path, page)) in self.pages.iter().enumerate() {
+            documents.push(json!({ "path": path, "title": page.title }));
+
+            for section in &page.sections {
+                let index = sections.len() as u32;
+                sections.push(json!([
+                    doc,
+                    section.anchor,
+                    section.heading,
+                    excerpt(&section.text, self.options.excerpt_len),
+                ]));
+
+                let mut counts: BTreeMap<String, u32> = BTreeMap::new();
+                for term in tokenize(&section.heading).chain(tokenize(&section.text)) {
+                    if term.chars().count() >= self.options.min_term_len {
+                        *counts.entry(term).or_default() += 1;
+                    }
+                }
+                for (term, count) in counts {
+                    terms.entry(term).or_default().extend([index, count]);
+                }
+            }
+        }
+
+        json!({
+            "documents": documents,
+            "sections": sections,
+            "terms": terms,
+        })
+    }
+
+    /// Build the JSON index as a compact string.
+    pub fn to_json_string(&self) -> String {
+        self.to_json().to_string()
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        let path = normalize_path(path);
+        let path = path.strip_prefix(&self.root).unwrap_or(&path);
+        path.components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Lowercased runs of alphanumeric characters.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// The first `len` characters of `text`, cut at a word boundary.
+fn excerpt(text: &str, len: usize) -> String {
+    if text.chars().count() <= len {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(len).collect();
+    let cut = match cut.rfind(' ') {
+        Some(i) if i > 0 => &cut[..i],
+        _ => &cut,
+    };
+    format!("{cut}…")
+}
+
+/// Accumulates sections while walking a document.
+struct Collector<'o> {
+    options: &'o SearchOptions,
+    sections: Vec<SearchSection>,
+    current: SearchSection,
+    /// Keys of the headings visited so far, to look up their anchors.
+    keys: HeadingKeys,
+}
+
+impl<'o> Collector<'o> {
+    fn new(options: &'o SearchOptions) -> Self {
+        Self {
+            options,
+            sections: Vec::new(),
+            current: SearchSection::default(),
+            keys: HeadingKeys::default(),
+        }
+    }
+
+    fn start_section(&mut self, heading: String, anchor: &str) {
+        let previous = std::mem::replace(
+            &mut self.current,
+            SearchSection {
+                heading: collapse_whitespace(&heading),
+                anchor: Some(anchor.to_string()),
+                text: String::new(),
+            },
+        );
+        self.push_section(previous);
+    }
+
+    fn push_section(&mut self, mut section: SearchSection) {
+        section.text.truncate(section.text.trim_end().len());
+        if section.anchor.is_some() || !section.text.is_empty() {
+            self.sections.push(section);
+        }
+    }
+
+    /// Append text, collapsing whitespace.
+    fn text(&mut self, text: &str) {
+        for c in text.chars() {
+            if c.is_whitespace() {
+                self.space();
+            } else {
+                self.current.text.push(c);
+            }
+        }
+    }
+
+    /// Separate words at element boundaries.
+    fn space(&mut self) {
+        if !self.current.text.is_empty() && !self.current.text.ends_with(' ') {
+            self.current.text.push(' ');
+        }
+    }
+
+    fn finish(mut self, title: Option<String>) -> SearchPage {
+        let current = std::mem::take(&mut self.current);
+        self.push_section(current);
+        let title = title.or_else(|| {
+            self.sections
+                .iter()
+                .find(|s| s.anchor.is_some())
+                .map(|s| s.heading.clone())
+        });
+        SearchPage {
+            title,
+            sections: self.sections,
+        }
+    }
+
+    fn html(&mut self, elem: HtmlElement<'_>, outline: &Outline) {
+        for child in elem.children() {
+            match child.kind() {
+                NodeKind::Element(child) => {
+                    let tag = child.tag();
+                    if matches!(tag.as_str(), "head" | "script" | "style" | "template")
+                        || (!self.options.include_code && matches!(tag.as_str(), "pre" | "code"))
+                    {
+                        continue;
+                    }
+                    if is_heading(child)
+                        && let Some(anchor) = self.keys.next(child.0.span).and_then(|key| outline.anchor(key))
+                    {
+                        self.start_section(html_text(child), anchor);
+                        continue;
+                    }
+                    self.space();
+                    self.html(child, outline);
+                    self.space();
+                }
+                NodeKind::Text(text) => self.text(text),
+                NodeKind::Frame(frame) if self.options.include_frames => {
+                    self.space();
+                    self.text(&frame_text(frame));
+                    self.space();
+                }
+                NodeKind::Frame(_) | NodeKind::Tag => {}
+            }
+        }
+    }
+
+    #[cfg(feature = "scan")]
+    fn content(&mut self, content: &typst::foundations::Content, outline: &Outline) {
+        use std::ops::ControlFlow;
+        use typst::foundations::Content;
+        use typst::math::EquationElem;
+        use typst::model::{HeadingElem, ParbreakElem};
+        use typst::text::{LinebreakElem, RawContent, RawElem, SpaceElem, TextElem};
+
+        // Remaining visits inside a subtree that was handled as a whole
+        let mut skip = 0usize;
+        let _ = content.traverse(&mut |elem: Content| {
+            // Count skipped headings too, so keys match the outline's
+            let key = if elem.is::<HeadingElem>() { self.keys.next(elem.span()) } else { None };
+            if skip > 0 {
+                skip -= 1;
+                return ControlFlow::<()>::Continue(());
+            }
+
+            if let Some(anchor) = key.and_then(|key| outline.anchor(key)) {
+                self.start_section(elem.plain_text().to_string(), anchor);
+                skip = subtree_len(&elem);
+            } else if let Some(raw) = elem.to_packed::<RawElem>() {
+                if self.options.include_code {
+                    self.space();
+                    match &raw.text {
+                        RawContent::Text(text) => self.text(text),
+                        RawContent::Lines(lines) => {
+                            for (line, _) in lines {
+                                self.text(line);
+                                self.space();
+                            }
+                        }
+                    }
+                    self.space();
+                }
+                skip = subtree_len(&elem);
+            } else if (elem.is::<EquationElem>() || elem.is::<typst_html::FrameElem>())
+                && !self.options.include_frames
+            {
+                skip = subtree_len(&elem);
+            } else if let Some(text) = elem.to_packed::<TextElem>() {
+                self.text(&text.text);
+            } else if elem.is::<SpaceElem>() || elem.is::<LinebreakElem>() || elem.is::<ParbreakElem>() {
+                self.space();
+            }
+            ControlFlow::Continue(())
+        });
+    }
+}
+
+/// Number of descendants of `elem` (excluding itself).
+#[cfg(feature = "scan")]
+fn subtree_len(elem: &typst::foundations::Content) -> usize {
+    let mut len = 0usize;
+    let _ = elem.traverse(&mut |_| {
+        len += 1;
+        std::ops::ControlFlow::<()>::Continue(())
+    });
+    len - 1
+}
+
+/// Plain text of an HTML element, ignoring frames.
+fn html_text(elem: HtmlElement<'_>) -> String {
+    elem.descendants().filter_map(|node| node.as_text()).collect()
+}
+
+/// Text of all glyph runs in a frame.
+fn frame_text(frame: HtmlFrame<'_>) -> String {
+    fn walk(frame: &Frame, out: &mut String) {
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => walk(&group.frame, out),
+                FrameItem::Text(text) => {
+                    out.push_str(&text.text);
+                    out.push(' ');
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut out = String::new();
+    walk(&frame.0.inner, &mut out);
+    out
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::compile::Compiler;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const SOURCE: &str = r#"#set document(title: "Guide")
+Welcome to the guide.
+= Install
+Run the installer twice. Install again!
+```sh
+cargo install secret-tool
+```
+#html.frame(rect(width: 4pt))
+== Linux <linux>
+Use the package manager.
+"#;
+
+    fn compile(dir: &TempDir, name: &str, source: &str) -> CompileResult {
+        let file = dir.path().join(name);
+        fs::write(&file, source).unwrap();
+        Compiler::new(dir.path()).with_path(&file).compile().unwrap()
+    }
+
+    #[test]
+    fn test_search_page_sections() {
+        let dir = TempDir::new().unwrap();
+        let page = compile(&dir, "guide.typ", SOURCE).search_page(&SearchOptions::default());
+
+        assert_eq!(page.title.as_deref(), Some("Guide"));
+        let sections: Vec<_> = page
+            .sections
+            .iter()
+            .map(|s| (s.anchor.as_deref(), s.heading.as_str(), s.text.as_str()))
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                (None, "", "Welcome to the guide."),
+                (Some("install"), "Install", "Run the installer twice. Install again!"),
+                (Some("linux"), "Linux", "Use the package manager."),
+            ]
+        );
+
+        let with_code = compile(&dir, "guide.typ", SOURCE).search_page(&SearchOptions::new().with_code(true));
+        assert!(with_code.sections[1].text.contains("cargo install secret-tool"));
+    }
+
+    #[cfg(feature = "scan")]
+    #[test]
+    fn test_search_page_from_scan() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("guide.typ");
+        fs::write(&file, SOURCE).unwrap();
+        let scanned = crate::process::scan::Scanner::new(dir.path()).scan(&file).unwrap();
+        let compiled = Compiler::new(dir.path()).with_path(&file).compile().unwrap();
+
+        let options = SearchOptions::default();
+        let from_scan = scanned.search_page(&options);
+        // The document title is a set rule and only known after layout
+        assert_eq!(from_scan.title.as_deref(), Some("Install"));
+        assert_eq!(from_scan.sections, compiled.search_page(&options).sections);
+    }
+
+    #[test]
+    fn test_search_page_loop_headings() {
+        let dir = TempDir::new().unwrap();
+        let source = "#for (name, body) in ((\"Step\", \"First.\"), (\"Step\", \"Second.\")) [= #name\n#body]";
+        let page = compile(&dir, "steps.typ", source).search_page(&SearchOptions::default());
+
+        // Headings from one loop share a span but keep their own anchors
+        let sections: Vec<_> = page
+            .sections
+            .iter()
+            .map(|s| (s.anchor.as_deref(), s.text.as_str()))
+            .collect();
+        assert_eq!(sections, [(Some("step"), "First."), (Some("step-1"), "Second.")]);
+
+        #[cfg(feature = "scan")]
+        {
+            let scanned = crate::process::scan::Scanner::new(dir.path())
+                .scan(dir.path().join("steps.typ"))
+                .unwrap();
+            assert_eq!(scanned.search_page(&SearchOptions::default()).sections, page.sections);
+        }
+    }
+
+    #[test]
+    fn test_search_index_json() {
+        let dir = TempDir::new().unwrap();
+        let pages = [dir.path().join("b.typ"), dir.path().join("a.typ")];
+        let results = vec![
+            Ok(compile(&dir, "b.typ", SOURCE)),
+            Err(crate::diagnostic::CompileError::Cancelled),
+        ];
+
+        let mut index = SearchIndex::new(dir.path()).with_options(SearchOptions::new().with_excerpt_len(10));
+        index.insert_results(&pages, &results);
+        index.insert(&pages[1], &compile(&dir, "a.typ", "= Install\nNothing here."));
+        assert_eq!(index.len(), 2);
+
+        let json = index.to_json();
+        assert_eq!(json["documents"][0], json!({ "path": "a.typ", "title": "Install" }));
+        assert_eq!(json["documents"][1]["path"], "b.typ");
+        assert_eq!(json["sections"][0], json!([0, "install", "Install", "Nothing…"]));
+        assert_eq!(json["sections"][2], json!([1, "install", "Install", "Run the…"]));
+
+        // section 0 once (heading), section 2 twice (heading + body)
+        assert_eq!(json["terms"]["install"], json!([0, 1, 2, 2]));
+        assert!(json["terms"].get("a").is_none());
+        assert!(json["terms"].get("secret").is_none());
+
+        assert!(index.remove(&pages[1]));
+        assert_eq!(index.to_json()["documents"].as_array().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,206 @@
+//! Per-phase timing and file statistics.
+//!
+//! - [`CompileStats`] - Timing and file access for a single compile/scan
+//! - [`BatchReport`] - Aggregated stats for a batch with slowest-files ranking
+
+use std::fmt;
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::world::FileStats;
+
+/// Timing and file access statistics for a single compilation or scan.
+///
+/// # Phases
+///
+/// - `eval`: Evaluating the main file into content. Measured separately
+///   only with the `scan` feature; otherwise it is included in `layout`.
+/// - `layout`: Layout/introspection iterations (zero for scans).
+/// - `export`: HTML/PDF serialization, recorded when `html()` / `pdf()` is called.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompileStats {
+    /// Time spent in Eval.
+    pub eval: Duration,
+    /// Time spent in Layout (including Eval without the `scan` feature).
+    pub layout: Duration,
+    /// Time spent exporting (HTML serialization or PDF export).
+    pub export: Duration,
+    /// Total pipeline time (excluding export).
+    pub total: Duration,
+    /// File access counters.
+    pub files: FileStats,
+}
+
+impl AddAssign for CompileStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.eval += rhs.eval;
+        self.layout += rhs.layout;
+        self.export += rhs.export;
+        self.total += rhs.total;
+        self.files += rhs.files;
+    }
+}
+
+/// Export time recorded after compilation by `html()` / `pdf()`.
+///
+/// Atomic so results stay `Sync` while `html(&self)` can record timing.
+#[derive(Debug, Default)]
+pub(crate) struct ExportTimer(AtomicU64);
+
+impl ExportTimer {
+    /// Add elapsed time to the recorded export time.
+    pub fn record(&self, elapsed: Duration) {
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.0.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// Get the recorded export time.
+    pub fn get(&self) -> Duration {
+        Duration::from_nanos(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Aggregated statistics for a batch, with slowest-files ranking.
+///
+/// # Example
+///
+/// ```ignore
+/// let (results, report) = batcher.batch_compile_with_report(&files)?;
+/// println!("{report}");
+/// for (path, stats) in report.slowest(5) {
+///     println!("{}: {:?}", path.display(), stats.total);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BatchReport {
+    entries: Vec<(PathBuf, CompileStats)>,
+    totals: CompileStats,
+    failed: usize,
+    wall: Duration,
+}
+
+impl BatchReport {
+    /// Build a report from per-file stats.
+    ///
+    /// `failed` counts files without stats; `wall` is the elapsed wall-clock
+    /// time of the whole batch (less than the summed totals when parallel).
+    pub fn from_stats<P, I>(entries: I, failed: usize, wall: Duration) -> Self
+    where
+        P: Into<PathBuf>,
+        I: IntoIterator<Item = (P, CompileStats)>,
+    {
+        let mut entries: Vec<_> = entries.into_iter().map(|(p, s)| (p.into(), s)).collect();
+        entries.sort_by_key(|(_, s)| std::cmp::Reverse(s.total));
+
+        let mut totals = CompileStats::default();
+        for (_, stats) in &entries {
+            totals += *stats;
+        }
+
+        Self {
+            entries,
+            totals,
+            failed,
+            wall,
+        }
+    }
+
+    /// Summed stats over all successful files.
+    pub fn totals(&self) -> &CompileStats {
+        &self.totals
+    }
+
+    /// Number of successful files.
+    pub fn succeeded(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Number of failed files (not included in totals or ranking).
+    pub fn failed(&self) -> usize {
+        self.failed
+    }
+
+    /// Wall-clock time of the whole batch.
+    pub fn wall(&self) -> Duration {
+        self.wall
+    }
+
+    /// The `n` slowest files by total time, slowest first.
+    pub fn slowest(&self, n: usize) -> impl Iterator<Item = (&Path, &CompileStats)> {
+        self.entries.iter().take(n).map(|(p, s)| (p.as_path(), s))
+    }
+
+    /// Stats for a specific file.
+    pub fn get(&self, path: &Path) -> Option<&CompileStats> {
+        self.entries.iter().find(|(p, _)| p == path).map(|(_, s)| s)
+    }
+}
+
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = &self.totals;
+        writeln!(
+            f,
+            "{} files ({} failed) in {:.2?}: eval {:.2?}, layout {:.2?}, export {:.2?}",
+            self.entries.len() + self.failed,
+            self.failed,
+            self.wall,
+            t.eval,
+            t.layout,
+            t.export,
+        )?;
+        writeln!(
+            f,
+            "files: {} read ({} bytes), {} snapshot hits, {} thread-local hits, {} cache hits",
+            t.files.files_read,
+            t.files.bytes_read,
+            t.files.snapshot_hits,
+            t.files.thread_local_hits,
+            t.files.cache_hits,
+        )?;
+        for (path, stats) in self.slowest(10) {
+            writeln!(f, "  {:>10.2?}  {}", stats.total, path.display())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(ms: u64) -> CompileStats {
+        CompileStats {
+            eval: Duration::from_millis(ms / 2),
+            layout: Duration::from_millis(ms / 2),
+            total: Duration::from_millis(ms),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_batch_report_ranking() {
+        let report = BatchReport::from_stats(
+            [("a.typ", stats(10)), ("b.typ", stats(30)), ("c.typ", stats(20))],
+            1,
+            Duration::from_millis(35),
+        );
+
+        let slowest: Vec<_> = report.slowest(2).map(|(p, _)| p).collect();
+        assert_eq!(slowest, [Path::new("b.typ"), Path::new("c.typ")]);
+        assert_eq!(report.totals().total, Duration::from_millis(60));
+        assert_eq!(report.succeeded(), 3);
+        assert_eq!(report.failed(), 1);
+        assert!(report.to_string().contains("b.typ"));
+    }
+
+    #[test]
+    fn test_export_timer() {
+        let timer = ExportTimer::default();
+        timer.record(Duration::from_millis(2));
+        timer.record(Duration::from_millis(3));
+        assert_eq!(timer.get(), Duration::from_millis(5));
+    }
+}
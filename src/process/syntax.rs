@@ -0,0 +1,421 @@
+//! Syntax-only scanning (skips Eval entirely).
+//!
+//! Reads the main file's syntax tree without evaluating it: no imports are
+//! loaded, no packages are downloaded and no code runs. This finds what is
+//! written literally in the file:
+//!
+//! - `#metadata(...) <label>` with a literal value (numbers, strings,
+//!   booleans, `none`, arrays and dictionaries of those)
+//! - `import` and `include` paths
+//! - Markup headings (`= Title`)
+//!
+//! Anything produced by code (a template function emitting metadata,
+//! `#heading[...]`, show rules) is not seen. Use
+//! [`Scanner::scan`](super::scan::Scanner::scan) for those, or
+//! [`Scanner::scan_metadata`](super::scan::Scanner::scan_metadata) to fall
+//! back to a full scan when a labeled value is not a literal or may come
+//! from code.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let syntax = Scanner::new(root).scan_syntax(path)?;
+//! let draft = syntax.metadata("draft") == Some(serde_json::json!(true));
+//! ```
+
+use serde_json::{Map as JsonMap, Number, Value as JsonValue};
+use typst::syntax::ast::{self, AstNode};
+use typst::syntax::{Source, SyntaxNode};
+use typst::World;
+
+use super::scan::Heading;
+use crate::diagnostic::{CompileError, SourceLocation, SpanResolver};
+use crate::world::TypstWorld;
+
+/// Result of a syntax-only scan.
+#[derive(Debug, Clone, Default)]
+pub struct SyntaxScan {
+    metadata: Vec<SyntaxMetadata>,
+    imports: Vec<String>,
+    headings: Vec<Heading>,
+    literal: bool,
+    code: bool,
+}
+
+/// A labeled `#metadata(...)` call found in the syntax tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxMetadata {
+    /// The attached label (without angle brackets).
+    pub label: String,
+    /// The value, or `None` if it is not a literal and needs evaluation.
+    pub value: Option<JsonValue>,
+    /// Where the call is written in the source.
+    pub location: Option<SourceLocation>,
+}
+
+impl SyntaxScan {
+    /// Get the literal metadata value for a label.
+    ///
+    /// Returns `None` if the label is missing or its value is not a literal
+    /// (see [`metadata_entry`](Self::metadata_entry) to tell them apart).
+    pub fn metadata(&self, label: &str) -> Option<JsonValue> {
+        self.metadata_entry(label)?.value.clone()
+    }
+
+    /// Get the first metadata call with the given label.
+    pub fn metadata_entry(&self, label: &str) -> Option<&SyntaxMetadata> {
+        self.metadata.iter().find(|meta| meta.label == label)
+    }
+
+    /// Get all labeled metadata calls in source order.
+    pub fn all_metadata(&self) -> &[SyntaxMetadata] {
+        &self.metadata
+    }
+
+    /// Get import and include paths as written (`"@preview/cetz:0.3.0"`, `"../base.typ"`).
+    ///
+    /// Imports with a computed path are skipped.
+    pub fn imports(&self) -> &[String] {
+        &self.imports
+    }
+
+    /// Get markup headings in source order.
+    ///
+    /// Code inside a heading (`= Hello #name`) is left out of its text;
+    /// [`is_literal`](Self::is_literal) reports whether that happened.
+    pub fn headings(&self) -> &[Heading] {
+        &self.headings
+    }
+
+    /// Check if every metadata value and heading was read without evaluation.
+    pub fn is_literal(&self) -> bool {
+        self.literal
+    }
+
+    /// Check if the file calls functions, applies show rules or imports
+    /// other files, any of which may produce content this scan does not see.
+    ///
+    /// Calls to `metadata` itself are not counted.
+    pub fn has_code(&self) -> bool {
+        self.code
+    }
+}
+
+/// Internal syntax scan implementation, shared by [`Scanner`](super::scan::Scanner).
+pub(crate) fn scan_syntax_impl(world: &TypstWorld) -> Result<SyntaxScan, CompileError> {
+    world.check_interrupt()?;
+    let line_offset = world.prelude_line_count();
+    let source = world
+        .source(world.main())
+        .map_err(|e| CompileError::html_export(format!("Failed to read source: {e:?}")))?;
+
+    let errors = source.root().errors();
+    if !errors.is_empty() {
+        let diags = errors.into_iter().map(Into::into).collect();
+        return Err(CompileError::compilation_with_offset(world, diags, line_offset));
+    }
+
+    Ok(SyntaxScanner::new(&source, line_offset).scan(&source))
+}
+
+struct SyntaxScanner {
+    resolver: SpanResolver,
+    scan: SyntaxScan,
+}
+
+impl SyntaxScanner {
+    fn new(source: &Source, line_offset: usize) -> Self {
+        Self {
+            resolver: SpanResolver::from_source(source.clone(), line_offset),
+            scan: SyntaxScan {
+                literal: true,
+                ..SyntaxScan::default()
+            },
+        }
+    }
+
+    fn scan(mut self, source: &Source) -> SyntaxScan {
+        self.imports(source.root());
+        self.scan.code = has_code(source.root());
+        if let Some(markup) = source.root().cast::<ast::Markup>() {
+            self.markup(markup);
+        }
+        self.scan
+    }
+
+    /// Collect import paths anywhere in the tree, in source order.
+    fn imports(&mut self, node: &SyntaxNode) {
+        let path = if let Some(import) = node.cast::<ast::ModuleImport>() {
+            Some(import.source())
+        } else {
+            node.cast::<ast::ModuleInclude>().map(|include| include.source())
+        };
+        if let Some(path) = path {
+            if let ast::Expr::Str(path) = path {
+                self.scan.imports.push(path.get().to_string());
+            }
+            return;
+        }
+        for child in node.children() {
+            self.imports(child);
+        }
+    }
+
+    /// Walk markup that is always shown (not inside code).
+    fn markup(&mut self, markup: ast::Markup<'_>) {
+        // Last labellable expression, for `<label>` attachment
+        let mut last: Option<ast::Expr<'_>> = None;
+        for expr in markup.exprs() {
+            match expr {
+                ast::Expr::Space(_) | ast::Expr::Parbreak(_) => continue,
+                ast::Expr::Label(label) => {
+                    if let Some(ast::Expr::FuncCall(call)) = last {
+                        self.metadata(call, label.get());
+                    }
+                }
+                ast::Expr::Heading(heading) => self.heading(heading),
+                ast::Expr::ContentBlock(block) => self.markup(block.body()),
+                ast::Expr::Strong(strong) => self.markup(strong.body()),
+                ast::Expr::Emph(emph) => self.markup(emph.body()),
+                ast::Expr::ListItem(item) => self.markup(item.body()),
+                ast::Expr::EnumItem(item) => self.markup(item.body()),
+                ast::Expr::TermItem(item) => {
+                    self.markup(item.term());
+                    self.markup(item.description());
+                }
+                _ => {}
+            }
+            last = Some(expr);
+        }
+    }
+
+    fn metadata(&mut self, call: ast::FuncCall<'_>, label: &str) {
+        if !matches!(call.callee(), ast::Expr::Ident(ident) if ident.get() == "metadata") {
+            return;
+        }
+        let mut args = call.args().items();
+        let value = match (args.next(), args.next()) {
+            (Some(ast::Arg::Pos(value)), None) => literal(value),
+            _ => None,
+        };
+        self.scan.literal &= value.is_some();
+        self.scan.metadata.push(SyntaxMetadata {
+            label: label.to_string(),
+            value,
+            location: self.resolver.resolve(call.span()),
+        });
+    }
+
+    fn heading(&mut self, heading: ast::Heading<'_>) {
+        let mut text = String::new();
+        self.scan.literal &= plain_text(heading.body(), &mut text);
+        self.scan.headings.push(Heading {
+            level: heading.depth().get().min(u8::MAX as usize) as u8,
+            text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+            supplement: None,
+            location: self.resolver.resolve(heading.span()),
+        });
+    }
+}
+
+/// Check for calls (other than `metadata`), show rules, imports and includes.
+fn has_code(node: &SyntaxNode) -> bool {
+    match node.cast::<ast::Expr>() {
+        Some(ast::Expr::FuncCall(call))
+            if !matches!(call.callee(), ast::Expr::Ident(ident) if ident.get() == "metadata") =>
+        {
+            true
+        }
+        Some(ast::Expr::ShowRule(_) | ast::Expr::ModuleImport(_) | ast::Expr::ModuleInclude(_)) => true,
+        _ => node.children().any(has_code),
+    }
+}
+
+/// Append the plain text of `markup`. Returns `false` if code was skipped.
+fn plain_text(markup: ast::Markup<'_>, out: &mut String) -> bool {
+    let mut literal = true;
+    for expr in markup.exprs() {
+        match expr {
+            ast::Expr::Text(text) => out.push_str(text.get()),
+            ast::Expr::Space(_) | ast::Expr::Linebreak(_) => out.push(' '),
+            ast::Expr::Escape(escape) => out.push(escape.get()),
+            ast::Expr::Shorthand(shorthand) => out.push(shorthand.get()),
+            ast::Expr::SmartQuote(quote) => out.push(if quote.double() { '"' } else { '\'' }),
+            ast::Expr::Link(link) => out.push_str(link.get()),
+            ast::Expr::Raw(raw) => {
+                let lines: Vec<_> = raw.lines().map(|line| line.get().as_str()).collect();
+                out.push_str(&lines.join(" "));
+            }
+            ast::Expr::Strong(strong) => literal &= plain_text(strong.body(), out),
+            ast::Expr::Emph(emph) => literal &= plain_text(emph.body(), out),
+            ast::Expr::Label(_) => {}
+            _ => literal = false,
+        }
+    }
+    literal
+}
+
+/// Convert a literal expression to JSON, as `serde_json` would serialize the
+/// evaluated value. Returns `None` for anything that needs evaluation.
+fn literal(expr: ast::Expr<'_>) -> Option<JsonValue> {
+    Some(match expr {
+        ast::Expr::None(_) => JsonValue::Null,
+        ast::Expr::Bool(v) => JsonValue::Bool(v.get()),
+        ast::Expr::Int(v) => JsonValue::from(v.get()),
+        ast::Expr::Float(v) => JsonValue::Number(Number::from_f64(v.get())?),
+        ast::Expr::Str(v) => JsonValue::String(v.get().to_string()),
+        ast::Expr::Parenthesized(v) => literal(v.expr())?,
+        ast::Expr::Unary(unary) => match (unary.op(), literal(unary.expr())?) {
+            (ast::UnOp::Pos, value @ JsonValue::Number(_)) => value,
+            (ast::UnOp::Neg, JsonValue::Number(n)) => match n.as_i64() {
+                Some(i) => JsonValue::from(i.checked_neg()?),
+                None => JsonValue::Number(Number::from_f64(-n.as_f64()?)?),
+            },
+            _ => return None,
+        },
+        ast::Expr::Array(array) => JsonValue::Array(
+            array
+                .items()
+                .map(|item| match item {
+                    ast::ArrayItem::Pos(expr) => literal(expr),
+                    ast::ArrayItem::Spread(_) => None,
+                })
+                .collect::<Option<_>>()?,
+        ),
+        ast::Expr::Dict(dict) => {
+            let mut map = JsonMap::new();
+            for item in dict.items() {
+                let (key, value) = match item {
+                    ast::DictItem::Named(named) => (named.name().get().to_string(), named.expr()),
+                    ast::DictItem::Keyed(keyed) => match keyed.key() {
+                        ast::Expr::Str(key) => (key.get().to_string(), keyed.expr()),
+                        _ => return None,
+                    },
+                    ast::DictItem::Spread(_) => return None,
+                };
+                map.insert(key, literal(value)?);
+            }
+            JsonValue::Object(map)
+        }
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::scan::Scanner;
+    use serde_json::json;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_literal_metadata() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("post.typ");
+        fs::write(
+            &file,
+            r#"#import "@preview/nonexistent:0.1.0": *
+#metadata((title: "Hello", tags: ("a", "b"), draft: false, "n k": -1.5, count: -(2))) <meta>
+#metadata(1pt) <size>
+#metadata(none)
+
+<empty>
+"#,
+        )
+        .unwrap();
+
+        // Would fail to evaluate: the package doesn't exist
+        let scan = Scanner::new(dir.path()).scan_syntax(&file).unwrap();
+        assert_eq!(
+            scan.metadata("meta"),
+            Some(json!({"title": "Hello", "tags": ["a", "b"], "draft": false, "n k": -1.5, "count": -2}))
+        );
+        assert_eq!(scan.metadata("empty"), Some(JsonValue::Null));
+        assert_eq!(scan.metadata("size"), None);
+        assert!(scan.metadata_entry("size").unwrap().value.is_none());
+        assert!(scan.metadata_entry("missing").is_none());
+        assert!(!scan.is_literal());
+        assert_eq!(scan.imports(), ["@preview/nonexistent:0.1.0"]);
+
+        let location = scan.metadata_entry("meta").unwrap().location.as_ref().unwrap();
+        assert_eq!((location.path.as_str(), location.line, location.column), ("post.typ", 2, 2));
+    }
+
+    #[test]
+    fn test_matches_full_scan() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("base.typ"), "#let x = 1").unwrap();
+        let source = r#"#import "base.typ": x
+#include "base.typ"
+#metadata((title: "Post", date: "2024-01-01", views: 3, ratio: 0.5)) <meta>
+= Hello *world* -- again
+Text
+#[== Nested `code`]
+#if false [= Hidden]
+"#;
+        let file = dir.path().join("post.typ");
+        fs::write(&file, source).unwrap();
+
+        let syntax = Scanner::new(dir.path()).scan_syntax(&file).unwrap();
+        let full = Scanner::new(dir.path()).scan(&file).unwrap();
+
+        assert!(syntax.is_literal());
+        assert_eq!(syntax.imports(), ["base.typ", "base.typ"]);
+        assert_eq!(syntax.metadata("meta"), full.metadata("meta"));
+
+        let texts = |headings: &[Heading]| -> Vec<(u8, String)> {
+            headings.iter().map(|h| (h.level, h.text.clone())).collect()
+        };
+        assert_eq!(texts(syntax.headings()), texts(&full.headings()));
+        assert_eq!(syntax.headings()[1].location.as_ref().unwrap().line, 6);
+    }
+
+    #[test]
+    fn test_scan_metadata_fallback() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("post.typ");
+        fs::write(
+            &file,
+            "#let year = 2024\n#metadata((year: year + 1)) <meta>\n#metadata(\"x\") <plain>",
+        )
+        .unwrap();
+
+        let scanner = || Scanner::new(dir.path());
+        assert_eq!(scanner().scan_syntax(&file).unwrap().metadata("meta"), None);
+        assert_eq!(scanner().scan_metadata(&file, "meta").unwrap(), Some(json!({"year": 2025})));
+        assert_eq!(scanner().scan_metadata(&file, "plain").unwrap(), Some(json!("x")));
+        assert_eq!(scanner().scan_metadata(&file, "missing").unwrap(), None);
+        assert!(!scanner().scan_syntax(&file).unwrap().has_code());
+    }
+
+    #[test]
+    fn test_scan_metadata_from_code() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("template.typ"),
+            "#let post(title: none, body) = [#metadata((title: title)) <meta>#body]",
+        )
+        .unwrap();
+        let file = dir.path().join("post.typ");
+        fs::write(&file, "#import \"template.typ\": post\n#show: post.with(title: \"Hi\")\nText").unwrap();
+
+        // The label only appears in the template, so the syntax scan misses it
+        let scanner = || Scanner::new(dir.path());
+        let syntax = scanner().scan_syntax(&file).unwrap();
+        assert!(syntax.metadata_entry("meta").is_none());
+        assert!(syntax.has_code());
+        assert_eq!(scanner().scan_metadata(&file, "meta").unwrap(), Some(json!({"title": "Hi"})));
+        assert_eq!(scanner().scan_metadata(&file, "missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("bad.typ");
+        fs::write(&file, "#metadata((a: 1) <meta>").unwrap();
+
+        let err = Scanner::new(dir.path()).scan_syntax(&file).unwrap_err();
+        assert!(err.has_fatal_errors());
+    }
+}
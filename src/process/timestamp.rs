@@ -0,0 +1,58 @@
+//! Shared `with_timestamp` / `reproducible` methods via trait.
+
+use crate::world::{Timestamp, WorldBuilder};
+
+/// Timestamp and reproducibility settings held by compile/scan builders.
+#[derive(Debug, Clone, Default)]
+pub struct TimestampSettings {
+    timestamp: Option<Timestamp>,
+    reproducible: bool,
+}
+
+impl TimestampSettings {
+    /// Apply the settings to a world builder.
+    pub(crate) fn apply(&self, mut builder: WorldBuilder) -> WorldBuilder {
+        if let Some(timestamp) = self.timestamp {
+            builder = builder.with_timestamp(timestamp);
+        }
+        if self.reproducible {
+            builder = builder.reproducible();
+        }
+        builder
+    }
+}
+
+/// Trait for types that configure `datetime.today()` and reproducible output.
+///
+/// Provides `with_timestamp` and `reproducible` methods.
+pub trait WithTimestamp: Sized {
+    /// Get mutable reference to the timestamp settings.
+    fn timestamp_mut(&mut self) -> &mut TimestampSettings;
+
+    /// Set a fixed timestamp for `datetime.today()`.
+    ///
+    /// Without a timestamp, `datetime.today()` returns `None` (compile error).
+    fn with_timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp_mut().timestamp = Some(timestamp);
+        self
+    }
+
+    /// Enable reproducible mode for every world built by this builder.
+    ///
+    /// Reads `SOURCE_DATE_EPOCH` unless `with_timestamp()` is set, and
+    /// evaluates `datetime.today()` in UTC. See [`WorldBuilder::reproducible`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // SOURCE_DATE_EPOCH=1700000000 cargo run
+    /// let results = Compiler::new(root)
+    ///     .reproducible()
+    ///     .into_batch()
+    ///     .batch_compile(&files)?;
+    /// ```
+    fn reproducible(mut self) -> Self {
+        self.timestamp_mut().reproducible = true;
+        self
+    }
+}
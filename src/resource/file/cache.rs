@@ -156,9 +156,19 @@ impl FileSlot {
 
     /// Retrieve parsed source using the global virtual file system.
     pub fn source_with_global_virtual(&mut self, project_root: &Path) -> FileResult<Source> {
+        self.source_with_global_virtual_tracked(project_root, |_| {})
+    }
+
+    /// Same as [`source_with_global_virtual`](Self::source_with_global_virtual),
+    /// reporting the size of each (re-)read of the file to `on_read`.
+    pub(crate) fn source_with_global_virtual_tracked(
+        &mut self,
+        project_root: &Path,
+        on_read: impl FnOnce(usize),
+    ) -> FileResult<Source> {
         record_file_access(self.id);
         self.source.get_or_init(
-            || read_with_global_virtual(self.id, project_root),
+            || tracked(read_with_global_virtual(self.id, project_root), on_read),
             |data, prev| {
                 let text = decode_utf8(&data)?;
                 match prev {
@@ -201,9 +211,19 @@ impl FileSlot {
 
     /// Retrieve raw bytes using the global virtual file system.
     pub fn file_with_global_virtual(&mut self, project_root: &Path) -> FileResult<Bytes> {
+        self.file_with_global_virtual_tracked(project_root, |_| {})
+    }
+
+    /// Same as [`file_with_global_virtual`](Self::file_with_global_virtual),
+    /// reporting the size of each (re-)read of the file to `on_read`.
+    pub(crate) fn file_with_global_virtual_tracked(
+        &mut self,
+        project_root: &Path,
+        on_read: impl FnOnce(usize),
+    ) -> FileResult<Bytes> {
         record_file_access(self.id);
         self.file.get_or_init(
-            || read_with_global_virtual(self.id, project_root),
+            || tracked(read_with_global_virtual(self.id, project_root), on_read),
             |data, _| Ok(Bytes::new(data)),
         )
     }
@@ -222,6 +242,14 @@ impl FileSlot {
     }
 }
 
+/// Report the size of a successful read.
+fn tracked(result: FileResult<Vec<u8>>, on_read: impl FnOnce(usize)) -> FileResult<Vec<u8>> {
+    if let Ok(data) = &result {
+        on_read(data.len());
+    }
+    result
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -17,7 +17,8 @@ use typst::syntax::VirtualPath;
 /// A semantic version number (major.minor.patch).
 ///
 /// Typst packages use strict semantic versioning with three numeric components.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PackageVersion {
     /// Major version number.
     pub major: u32,
@@ -65,7 +66,8 @@ impl std::fmt::Display for PackageVersion {
 ///     }
 /// }
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PackageId {
     namespace: String,
     name: String,
@@ -119,14 +119,13 @@ impl FontOptions {
     }
 }
 
-/// Sorting key for deterministic font ordering (for debugging).
+/// Sorting key for deterministic font ordering.
 ///
-/// Used by `sort_fonts_deterministically` to help diagnose font ordering issues.
-/// The actual font non-determinism problem was solved by ensuring fonts are
-/// initialized early with correct paths in serve mode.
-#[allow(dead_code)]
+/// `group` preserves the search priority (custom paths, then system fonts,
+/// then embedded fonts), so sorting never changes which font wins a lookup.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 struct FontSortKey {
+    group: u8,
     path: Option<PathBuf>,
     index: u32,
 }
@@ -237,6 +236,9 @@ fn init_fonts_impl(options: &FontOptions) -> (Fonts, LazyHash<FontBook>) {
     // Search custom paths and optionally system/embedded fonts
     let fonts = searcher.search_with(&paths);
 
+    // Directory iteration order is filesystem-dependent; sort for stable indices
+    let fonts = sort_fonts_deterministically(fonts, &options.custom_paths);
+
     // DEBUG: Dump font list for debugging
     // debug_dump_fonts(&fonts);
 
@@ -291,24 +293,20 @@ pub fn font_family_count() -> Option<usize> {
 }
 
 // =============================================================================
-// Font Sorting (For Debugging)
+// Font Sorting
 // =============================================================================
 
-/// Sort fonts by (path, index) for debugging font ordering issues.
+/// Sort fonts by (group, path, index) so font indices are stable across runs.
 ///
 /// # Background
 ///
-/// `fontdb` uses `std::fs::read_dir()` which doesn't guarantee order.
-/// This function can be used to debug font-related issues by ensuring
-/// consistent ordering.
-///
-/// # Note
+/// `fontdb` uses `std::fs::read_dir()` which doesn't guarantee order, so
+/// the same font set could otherwise get different indices (and different
+/// fallback picks among equal candidates) on each run.
 ///
-/// The actual font non-determinism problem in serve mode was solved by
-/// ensuring `warmup_fonts()` is called even when using vdom-cache,
-/// not by sorting fonts.
-#[allow(dead_code)]
-fn sort_fonts_deterministically(fonts: Fonts) -> Fonts {
+/// Output is only identical across machines if they load the same fonts,
+/// e.g. with `include_system_fonts: false` and bundled font directories.
+fn sort_fonts_deterministically(fonts: Fonts, custom_paths: &[PathBuf]) -> Fonts {
     let n = fonts.fonts.len();
     if n == 0 {
         return fonts;
@@ -323,6 +321,11 @@ fn sort_fonts_deterministically(fonts: Fonts) -> Fonts {
             (
                 i,
                 FontSortKey {
+                    group: match slot.path() {
+                        Some(path) if custom_paths.iter().any(|c| path.starts_with(c)) => 0,
+                        Some(_) => 1,
+                        None => 2,
+                    },
                     path: slot.path().map(|p| p.to_path_buf()),
                     index: slot.index(),
                 },
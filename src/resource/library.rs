@@ -57,6 +57,13 @@ pub static GLOBAL_LIBRARY: LazyLock<LazyHash<Library>> = LazyLock::new(|| {
     LazyHash::new(library)
 });
 
+/// Global shared library for paged export (PDF) - HTML feature disabled.
+///
+/// Used by paged compilation. HTML-only functions like `#html.elem` and
+/// `target()` are unavailable.
+pub static GLOBAL_PAGED_LIBRARY: LazyLock<LazyHash<Library>> =
+    LazyLock::new(|| LazyHash::new(Library::builder().build()));
+
 /// Create a library with custom `sys.inputs`.
 ///
 /// This creates a new `Library` instance with the specified inputs accessible
@@ -107,6 +114,15 @@ pub fn create_library_with_inputs(inputs: Dict) -> LazyHash<Library> {
     LazyHash::new(library)
 }
 
+/// Create a paged (non-HTML) library with custom `sys.inputs`.
+///
+/// Paged counterpart of [`create_library_with_inputs`], used for PDF export.
+#[cfg(feature = "pdf")]
+pub fn create_paged_library_with_inputs(inputs: Dict) -> LazyHash<Library> {
+    let library = Library::builder().with_inputs(inputs).build();
+    LazyHash::new(library)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -134,4 +150,10 @@ mod tests {
         // Should return the same static reference
         assert!(std::ptr::eq(lib1, lib2), "Library should be shared");
     }
+
+    #[test]
+    fn test_paged_library_has_no_html() {
+        assert!(GLOBAL_LIBRARY.global.scope().get("html").is_some());
+        assert!(GLOBAL_PAGED_LIBRARY.global.scope().get("html").is_none());
+    }
 }
@@ -2,13 +2,15 @@
 
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 use typst::foundations::Dict;
 
-use super::core::{Timestamp, TypstWorld};
+use super::cache::LocalCache;
+use super::core::{resolve_timestamp, source_date_epoch, Timestamp, TypstWorld};
+use super::interrupt::{CancellationToken, Interrupt};
 use super::snapshot::FileSnapshot;
 use super::strategy::{CacheStrategy, FontStrategy, LibraryStrategy};
-use crate::resource::library::create_library_with_inputs;
 
 /// Builder for configuring `TypstWorld`.
 ///
@@ -18,10 +20,16 @@ pub struct WorldBuilder {
     root: PathBuf,
     cache: Option<CacheStrategy>,
     fonts: Option<FontStrategy>,
-    library: LibraryStrategy,
+    inputs: Option<Dict>,
+    #[cfg(feature = "pdf")]
+    paged: bool,
     prelude: Option<String>,
     postlude: Option<String>,
     timestamp: Option<Timestamp>,
+    reproducible: bool,
+    cancellation: Option<CancellationToken>,
+    timeout: Option<Duration>,
+    main_text: Option<String>,
 }
 
 impl WorldBuilder {
@@ -32,10 +40,16 @@ impl WorldBuilder {
             root: root.to_path_buf(),
             cache: None,
             fonts: None,
-            library: LibraryStrategy::Global,
+            inputs: None,
+            #[cfg(feature = "pdf")]
+            paged: false,
             prelude: None,
             postlude: None,
             timestamp: None,
+            reproducible: false,
+            cancellation: None,
+            timeout: None,
+            main_text: None,
         }
     }
 
@@ -67,6 +81,16 @@ impl WorldBuilder {
         self
     }
 
+    /// Use a long-lived cache that persists across compilations.
+    ///
+    /// Entries are only reloaded after [`LocalCache::invalidate`].
+    ///
+    /// Best for: watch/serve mode via [`Project`](crate::process::project::Project).
+    pub fn with_project_cache(mut self, cache: Arc<LocalCache>) -> Self {
+        self.cache = Some(CacheStrategy::project(cache));
+        self
+    }
+
     // =========================================================================
     // Font Strategy
     // =========================================================================
@@ -98,13 +122,27 @@ impl WorldBuilder {
             .into_iter()
             .map(|(k, v)| (k.into(), v.into_value()))
             .collect();
-        self.library = LibraryStrategy::Custom(create_library_with_inputs(dict));
+        self.inputs = Some(dict);
         self
     }
 
     /// Configure `sys.inputs` from a pre-built `Dict`.
     pub fn with_inputs_dict(mut self, inputs: Dict) -> Self {
-        self.library = LibraryStrategy::Custom(create_library_with_inputs(inputs));
+        self.inputs = Some(inputs);
+        self
+    }
+
+    // =========================================================================
+    // Target
+    // =========================================================================
+
+    /// Use the paged (non-HTML) library for PDF export.
+    ///
+    /// The HTML feature is disabled, so HTML-only functions like `#html.elem`
+    /// and `target()` are unavailable. `sys.inputs` (if set) still apply.
+    #[cfg(feature = "pdf")]
+    pub fn paged(mut self) -> Self {
+        self.paged = true;
         self
     }
 
@@ -157,6 +195,26 @@ impl WorldBuilder {
         self
     }
 
+    /// Use in-memory text as the main file instead of reading it from disk.
+    ///
+    /// The main path only provides the file's identity: relative imports
+    /// resolve against its directory and diagnostics report its name.
+    /// No file needs to exist there; if one does, it is shadowed.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let world = TypstWorld::builder(&root.join("input.typ"), root)
+    ///     .with_local_cache()
+    ///     .with_fonts()
+    ///     .with_main_source("#import \"lib.typ\": title\n= #title")
+    ///     .build();
+    /// ```
+    pub fn with_main_source(mut self, text: impl Into<String>) -> Self {
+        self.main_text = Some(text.into());
+        self
+    }
+
     // =========================================================================
     // Timestamp
     // =========================================================================
@@ -170,14 +228,77 @@ impl WorldBuilder {
         self
     }
 
+    /// Enable reproducible mode.
+    ///
+    /// - Without an explicit [`with_timestamp`](Self::with_timestamp),
+    ///   `datetime.today()` uses `SOURCE_DATE_EPOCH` (or returns `None` if unset).
+    /// - `datetime.today()` without an offset uses UTC instead of the
+    ///   machine's local timezone.
+    ///
+    /// Together with deterministic font ordering (applied at font init),
+    /// identical inputs and fonts yield byte-identical output.
+    pub fn reproducible(mut self) -> Self {
+        self.reproducible = true;
+        self
+    }
+
+    // =========================================================================
+    // Interruption
+    // =========================================================================
+
+    /// Abort file access once the token is cancelled.
+    ///
+    /// Compile and scan pipelines then return [`CompileError::Cancelled`](crate::diagnostic::CompileError::Cancelled).
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Abort file access once `timeout` has elapsed since `build()`.
+    ///
+    /// Compile and scan pipelines then return [`CompileError::TimedOut`](crate::diagnostic::CompileError::TimedOut).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     /// Build the `TypstWorld`.
     ///
     /// # Panics
     ///
     /// Panics if cache or fonts strategy is not set.
-    pub fn build(self) -> TypstWorld {
+    pub fn build(mut self) -> TypstWorld {
+        let library = self.library_strategy();
         let cache = self.cache.expect("cache strategy must be set");
         let fonts = self.fonts.expect("fonts strategy must be set");
-        TypstWorld::new(&self.main_path, &self.root, cache, fonts, self.library, self.prelude, self.postlude, self.timestamp)
+        let interrupt = Interrupt::new(self.cancellation, self.timeout);
+        let timestamp = resolve_timestamp(self.timestamp, self.reproducible, source_date_epoch);
+        TypstWorld::new(
+            &self.main_path,
+            &self.root,
+            cache,
+            fonts,
+            library,
+            self.prelude,
+            self.postlude,
+            timestamp,
+            self.reproducible,
+            interrupt,
+            self.main_text,
+        )
+    }
+
+    fn library_strategy(&mut self) -> LibraryStrategy {
+        #[cfg(feature = "pdf")]
+        if self.paged {
+            return match self.inputs.take() {
+                Some(inputs) => LibraryStrategy::paged_with_inputs(inputs),
+                None => LibraryStrategy::GlobalPaged,
+            };
+        }
+        match self.inputs.take() {
+            Some(inputs) => LibraryStrategy::with_inputs(inputs),
+            None => LibraryStrategy::Global,
+        }
     }
 }
@@ -27,6 +27,22 @@ impl LocalCache {
     }
 }
 
+impl LocalCache {
+    /// Drop the cached source and bytes for a file.
+    ///
+    /// The next access reloads it from disk.
+    pub fn invalidate(&self, id: FileId) {
+        self.sources.write().unwrap().remove(&id);
+        self.files.write().unwrap().remove(&id);
+    }
+
+    /// Drop all cached entries.
+    pub fn clear(&self) {
+        self.sources.write().unwrap().clear();
+        self.files.write().unwrap().clear();
+    }
+}
+
 impl Default for LocalCache {
     fn default() -> Self {
         Self::new()
@@ -1,9 +1,10 @@
 //! Unified `TypstWorld` implementation.
 //!
 //! A single World implementation with configurable strategies for:
-//! - **Cache**: Local (task-local), Shared (global RwLock), Snapshot (pre-built + thread_local)
+//! - **Cache**: Local (task-local), Shared (global RwLock), Snapshot (pre-built + thread_local),
+//!   Project (long-lived, explicit invalidation)
 //! - **Fonts**: None (scan/query), Shared (build/serve)
-//! - **Library**: Global or Custom (with sys.inputs)
+//! - **Library**: Global, GlobalPaged (PDF export), or Custom (with sys.inputs)
 //!
 //! # Usage
 //!
@@ -49,14 +50,15 @@ use typst::{Library, World};
 
 use super::builder::WorldBuilder;
 use super::cache::{THREAD_LOCAL_FILES, THREAD_LOCAL_SOURCES};
+use super::interrupt::Interrupt;
 use super::path::normalize_path;
+use super::stats::{FileCounters, FileStats};
 use super::strategy::{CacheStrategy, FontStrategy, LibraryStrategy};
 use crate::resource::file::{
     decode_utf8, file_id_from_path, read_with_global_virtual, record_file_access, FileSlot,
     GLOBAL_FILE_CACHE,
 };
 use crate::resource::font::get_fonts;
-use crate::resource::library::GLOBAL_LIBRARY;
 
 // =============================================================================
 // Empty FontBook (for scan/query)
@@ -64,7 +66,7 @@ use crate::resource::library::GLOBAL_LIBRARY;
 
 static EMPTY_FONTBOOK: OnceLock<LazyHash<FontBook>> = OnceLock::new();
 
-fn empty_fontbook() -> &'static LazyHash<FontBook> {
+pub(crate) fn empty_fontbook() -> &'static LazyHash<FontBook> {
     EMPTY_FONTBOOK.get_or_init(|| LazyHash::new(FontBook::new()))
 }
 
@@ -78,6 +80,33 @@ fn empty_fontbook() -> &'static LazyHash<FontBook> {
 /// If not set, `datetime.today()` returns `None`.
 pub type Timestamp = DateTime<Utc>;
 
+/// Read the `SOURCE_DATE_EPOCH` environment variable as a timestamp.
+///
+/// Returns `None` if the variable is unset or not an integer number of
+/// seconds since the Unix epoch.
+/// See <https://reproducible-builds.org/specs/source-date-epoch/>.
+pub fn source_date_epoch() -> Option<Timestamp> {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|value| parse_epoch(&value))
+}
+
+/// Pick a world's timestamp: an explicit one wins, and `epoch` (normally
+/// [`source_date_epoch`]) is only consulted in reproducible mode.
+pub(crate) fn resolve_timestamp(
+    explicit: Option<Timestamp>,
+    reproducible: bool,
+    epoch: impl FnOnce() -> Option<Timestamp>,
+) -> Option<Timestamp> {
+    explicit.or_else(|| if reproducible { epoch() } else { None })
+}
+
+/// Parse seconds since the Unix epoch.
+fn parse_epoch(value: &str) -> Option<Timestamp> {
+    let seconds = value.trim().parse::<i64>().ok()?;
+    DateTime::from_timestamp(seconds, 0)
+}
+
 /// Unified Typst World with configurable strategies.
 ///
 /// Use `TypstWorld::builder()` for explicit configuration,
@@ -91,6 +120,13 @@ pub struct TypstWorld {
     prelude: Option<String>,
     postlude: Option<String>,
     timestamp: Option<Timestamp>,
+    /// Use UTC (instead of the local timezone) for `datetime.today()` without offset.
+    utc_today: bool,
+    interrupt: Interrupt,
+    /// In-memory main file (overrides the file on disk, if any).
+    /// The source has prelude/postlude injected; the bytes are the raw text.
+    main_source: Option<(Source, Bytes)>,
+    counters: FileCounters,
 }
 
 impl TypstWorld {
@@ -113,6 +149,9 @@ impl TypstWorld {
         prelude: Option<String>,
         postlude: Option<String>,
         timestamp: Option<Timestamp>,
+        utc_today: bool,
+        interrupt: Interrupt,
+        main_text: Option<String>,
     ) -> Self {
         let root = normalize_path(root);
         let main_abs = normalize_path(main_path);
@@ -121,6 +160,10 @@ impl TypstWorld {
             let filename = main_path.file_name().unwrap_or_default();
             FileId::new(None, VirtualPath::new(filename))
         });
+        let main_source = main_text.map(|text| {
+            let source = Source::new(main, inject_main(&text, prelude.as_deref(), postlude.as_deref()));
+            (source, Bytes::from_string(text))
+        });
 
         Self {
             root,
@@ -131,6 +174,10 @@ impl TypstWorld {
             prelude,
             postlude,
             timestamp,
+            utc_today,
+            interrupt,
+            main_source,
+            counters: FileCounters::default(),
         }
     }
 
@@ -150,14 +197,45 @@ impl TypstWorld {
             .unwrap_or(0)
     }
 
+    /// Get file access statistics accumulated by this world so far.
+    pub fn file_stats(&self) -> FileStats {
+        self.counters.snapshot()
+    }
+
+    /// Get the library strategy (e.g., to evaluate queries with the same library).
+    pub(crate) fn library_strategy(&self) -> &LibraryStrategy {
+        &self.library
+    }
+
+    /// Drop the cancellation token and deadline, e.g. to keep reading
+    /// sources after the pipeline finished.
+    pub(crate) fn without_interrupt(mut self) -> Self {
+        self.interrupt = Interrupt::default();
+        self
+    }
+
+    /// Check for cancellation or timeout.
+    ///
+    /// Called by the compile/scan pipelines between phases.
+    pub(crate) fn check_interrupt(&self) -> Result<(), crate::diagnostic::CompileError> {
+        self.interrupt.check()
+    }
+
     // =========================================================================
     // Cache Operations
     // =========================================================================
 
     fn get_source(&self, id: FileId) -> FileResult<Source> {
+        if let Some((source, _)) = &self.main_source
+            && id == self.main
+        {
+            return Ok(source.clone());
+        }
+        self.interrupt.check_file()?;
         match &self.cache {
             CacheStrategy::Local(local) => {
                 if let Some(source) = local.sources.read().unwrap().get(&id) {
+                    self.counters.cache_hit();
                     return Ok(source.clone());
                 }
                 let source = self.load_source(id)?;
@@ -170,18 +248,43 @@ impl TypstWorld {
                 if id == self.main && (self.prelude.is_some() || self.postlude.is_some()) {
                     return self.load_source(id);
                 }
+                self.counters.shared_lookup();
+                let mut read = false;
                 let mut cache = GLOBAL_FILE_CACHE.write();
                 let slot = cache.entry(id).or_insert_with(|| FileSlot::new(id));
-                slot.source_with_global_virtual(&self.root)
+                let source = slot.source_with_global_virtual_tracked(&self.root, |bytes| {
+                    read = true;
+                    self.counters.read(bytes);
+                });
+                if !read && source.is_ok() {
+                    self.counters.cache_hit();
+                }
+                source
+            }
+            CacheStrategy::Project(cache) => {
+                // Main file with prelude/postlude is world-specific; don't share it
+                if id == self.main && (self.prelude.is_some() || self.postlude.is_some()) {
+                    return self.load_source(id);
+                }
+                if let Some(source) = cache.sources.read().unwrap().get(&id) {
+                    record_file_access(id);
+                    self.counters.cache_hit();
+                    return Ok(source.clone());
+                }
+                let source = self.load_source(id)?;
+                cache.sources.write().unwrap().insert(id, source.clone());
+                Ok(source)
             }
             CacheStrategy::Snapshot(snapshot) => {
                 if let Some(source) = snapshot.get_source(id) {
                     record_file_access(id);
+                    self.counters.snapshot_hit();
                     return Ok(source);
                 }
                 let local_hit =
                     THREAD_LOCAL_SOURCES.with(|c| c.borrow().get(&id).cloned());
                 if let Some(source) = local_hit {
+                    self.counters.thread_local_hit();
                     return Ok(source);
                 }
                 let source = self.load_source(id)?;
@@ -192,9 +295,16 @@ impl TypstWorld {
     }
 
     fn get_file(&self, id: FileId) -> FileResult<Bytes> {
+        if let Some((_, bytes)) = &self.main_source
+            && id == self.main
+        {
+            return Ok(bytes.clone());
+        }
+        self.interrupt.check_file()?;
         match &self.cache {
             CacheStrategy::Local(local) => {
                 if let Some(bytes) = local.files.read().unwrap().get(&id) {
+                    self.counters.cache_hit();
                     return Ok(bytes.clone());
                 }
                 let bytes = self.load_file(id)?;
@@ -202,17 +312,38 @@ impl TypstWorld {
                 Ok(bytes)
             }
             CacheStrategy::Shared => {
+                self.counters.shared_lookup();
+                let mut read = false;
                 let mut cache = GLOBAL_FILE_CACHE.write();
                 let slot = cache.entry(id).or_insert_with(|| FileSlot::new(id));
-                slot.file_with_global_virtual(&self.root)
+                let bytes = slot.file_with_global_virtual_tracked(&self.root, |len| {
+                    read = true;
+                    self.counters.read(len);
+                });
+                if !read && bytes.is_ok() {
+                    self.counters.cache_hit();
+                }
+                bytes
+            }
+            CacheStrategy::Project(cache) => {
+                if let Some(bytes) = cache.files.read().unwrap().get(&id) {
+                    record_file_access(id);
+                    self.counters.cache_hit();
+                    return Ok(bytes.clone());
+                }
+                let bytes = self.load_file(id)?;
+                cache.files.write().unwrap().insert(id, bytes.clone());
+                Ok(bytes)
             }
             CacheStrategy::Snapshot(snapshot) => {
                 if let Some(bytes) = snapshot.get_file(id) {
                     record_file_access(id);
+                    self.counters.snapshot_hit();
                     return Ok(bytes);
                 }
                 let local_hit = THREAD_LOCAL_FILES.with(|c| c.borrow().get(&id).cloned());
                 if let Some(bytes) = local_hit {
+                    self.counters.thread_local_hit();
                     return Ok(bytes);
                 }
                 let bytes = self.load_file(id)?;
@@ -225,21 +356,12 @@ impl TypstWorld {
     fn load_source(&self, id: FileId) -> FileResult<Source> {
         record_file_access(id);
         let bytes = read_with_global_virtual(id, &self.root)?;
+        self.counters.read(bytes.len());
         let text = decode_utf8(&bytes)?;
 
         // Inject prelude/postlude for main file (fallback for non-snapshot usage)
         let text = if id == self.main {
-            let mut result = String::new();
-            if let Some(prelude) = &self.prelude {
-                result.push_str(prelude);
-                result.push('\n');
-            }
-            result.push_str(text);
-            if let Some(postlude) = &self.postlude {
-                result.push('\n');
-                result.push_str(postlude);
-            }
-            result
+            inject_main(text, self.prelude.as_deref(), self.postlude.as_deref())
         } else {
             text.into()
         };
@@ -250,20 +372,33 @@ impl TypstWorld {
     fn load_file(&self, id: FileId) -> FileResult<Bytes> {
         record_file_access(id);
         let data = read_with_global_virtual(id, &self.root)?;
+        self.counters.read(data.len());
         Ok(Bytes::new(data))
     }
 }
 
+/// Wrap main file text with prelude/postlude.
+fn inject_main(text: &str, prelude: Option<&str>, postlude: Option<&str>) -> String {
+    let mut result = String::new();
+    if let Some(prelude) = prelude {
+        result.push_str(prelude);
+        result.push('\n');
+    }
+    result.push_str(text);
+    if let Some(postlude) = postlude {
+        result.push('\n');
+        result.push_str(postlude);
+    }
+    result
+}
+
 // =============================================================================
 // World Trait Implementation
 // =============================================================================
 
 impl World for TypstWorld {
     fn library(&self) -> &LazyHash<Library> {
-        match &self.library {
-            LibraryStrategy::Global => &GLOBAL_LIBRARY,
-            LibraryStrategy::Custom(lib) => lib,
-        }
+        self.library.library()
     }
 
     fn book(&self) -> &LazyHash<FontBook> {
@@ -297,6 +432,7 @@ impl World for TypstWorld {
         let now = self.timestamp.as_ref()?;
 
         let with_offset = match offset {
+            None if self.utc_today => now.fixed_offset(),
             None => now.with_timezone(&Local).fixed_offset(),
             Some(hours) => {
                 let seconds = i32::try_from(hours).ok()?.checked_mul(3600)?;
@@ -311,3 +447,60 @@ impl World for TypstWorld {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_epoch() {
+        let ts = parse_epoch("1700000000").unwrap();
+        assert_eq!(ts.to_rfc3339(), "2023-11-14T22:13:20+00:00");
+        assert_eq!(parse_epoch(" 0\n").unwrap().timestamp(), 0);
+        assert!(parse_epoch("yesterday").is_none());
+        assert!(parse_epoch("").is_none());
+        assert!(parse_epoch("1.5").is_none());
+    }
+
+    #[test]
+    fn test_resolve_timestamp_precedence() {
+        // `epoch` stands in for `SOURCE_DATE_EPOCH`, so the env stays untouched
+        let explicit = parse_epoch("100");
+        let epoch = || parse_epoch("200");
+        assert_eq!(resolve_timestamp(explicit, true, epoch), explicit);
+        assert_eq!(resolve_timestamp(explicit, false, epoch), explicit);
+        assert_eq!(resolve_timestamp(None, true, epoch), epoch());
+        assert_eq!(resolve_timestamp(None, false, epoch), None);
+        assert_eq!(resolve_timestamp(None, true, || None), None);
+    }
+
+    #[test]
+    fn test_reproducible_today_uses_utc() {
+        // 23:30 UTC: the local date may differ, the UTC date must not
+        let ts = parse_epoch("1700004600").unwrap();
+        let world = TypstWorld::builder(Path::new("main.typ"), Path::new("."))
+            .with_local_cache()
+            .no_fonts()
+            .with_timestamp(ts)
+            .reproducible()
+            .build();
+
+        assert_eq!(world.today(None), Datetime::from_ymd(2023, 11, 14));
+        assert_eq!(world.today(Some(1)), Datetime::from_ymd(2023, 11, 15));
+    }
+
+    #[test]
+    fn test_interrupt_keeps_main_source() {
+        let world = TypstWorld::builder(Path::new("main.typ"), Path::new("."))
+            .with_local_cache()
+            .no_fonts()
+            .with_timeout(std::time::Duration::ZERO)
+            .with_main_source("= Main")
+            .build();
+
+        assert!(world.source(world.main()).is_ok());
+        assert!(world.file(world.main()).is_ok());
+        let other = FileId::new(None, VirtualPath::new("other.typ"));
+        assert!(world.source(other).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+//! Cooperative cancellation and wall-clock timeouts.
+//!
+//! Typst has no preemption hook, so interruption is cooperative:
+//! - `TypstWorld` checks at every file/source access and fails the access
+//!   (the in-memory main file is always returned)
+//! - The compile/scan pipeline checks before each phase, and after the
+//!   last one only if it failed (a refused file access surfaces as errors)
+//!
+//! Evaluation itself is never interrupted: infinite recursion or a huge
+//! loop that touches no files runs until its phase returns, and is only
+//! stopped at the next phase boundary. A phase that succeeds is kept even
+//! if it finished past the deadline.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use typst::diag::FileError;
+use typst::ecow::eco_format;
+
+use crate::diagnostic::CompileError;
+
+/// Shared flag for cancelling in-flight compilations.
+///
+/// Cloning is cheap; all clones observe the same flag.
+///
+/// # Example
+///
+/// ```ignore
+/// let token = CancellationToken::new();
+/// let batcher = Batcher::new(root).with_cancellation(token.clone());
+///
+/// // From another thread (e.g., Ctrl-C handler):
+/// token.cancel();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a new, non-cancelled token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of all compilations observing this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Check if cancellation has been requested.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Interruption settings for a single compilation.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Interrupt {
+    token: Option<CancellationToken>,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+impl Interrupt {
+    /// Create interruption settings; the deadline starts counting now.
+    pub fn new(token: Option<CancellationToken>, timeout: Option<Duration>) -> Self {
+        Self {
+            token,
+            timeout,
+            deadline: timeout.map(|t| Instant::now() + t),
+        }
+    }
+
+    /// Return the interruption error if cancelled or past the deadline.
+    pub fn check(&self) -> Result<(), CompileError> {
+        if self.token.as_ref().is_some_and(CancellationToken::is_cancelled) {
+            return Err(CompileError::Cancelled);
+        }
+        if let (Some(deadline), Some(timeout)) = (self.deadline, self.timeout)
+            && Instant::now() >= deadline
+        {
+            return Err(CompileError::TimedOut { timeout });
+        }
+        Ok(())
+    }
+
+    /// Same as [`check`](Self::check), but as a file error for `World` methods.
+    pub fn check_file(&self) -> Result<(), FileError> {
+        self.check()
+            .map_err(|e| FileError::Other(Some(eco_format!("{e}"))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_token_shared() {
+        let token = CancellationToken::new();
+        let interrupt = Interrupt::new(Some(token.clone()), None);
+        assert!(interrupt.check().is_ok());
+
+        token.cancel();
+        assert!(matches!(interrupt.check(), Err(CompileError::Cancelled)));
+        assert!(interrupt.check_file().is_err());
+    }
+
+    #[test]
+    fn test_timeout() {
+        let interrupt = Interrupt::new(None, Some(Duration::ZERO));
+        assert!(matches!(interrupt.check(), Err(CompileError::TimedOut { .. })));
+
+        let interrupt = Interrupt::new(None, Some(Duration::from_secs(3600)));
+        assert!(interrupt.check().is_ok());
+    }
+}
@@ -3,13 +3,19 @@
 mod builder;
 mod cache;
 mod core;
+mod interrupt;
 mod path;
 mod snapshot;
+mod stats;
 mod strategy;
 
 pub use builder::WorldBuilder;
 pub use cache::{clear_thread_local_cache, LocalCache};
-pub use core::{Timestamp, TypstWorld};
+pub use core::{source_date_epoch, Timestamp, TypstWorld};
+pub(crate) use core::empty_fontbook;
+pub use interrupt::CancellationToken;
 pub use path::normalize_path;
+pub(crate) use path::join_in_root;
 pub use snapshot::{FileSnapshot, SnapshotConfig, SnapshotError};
+pub use stats::FileStats;
 pub use strategy::{CacheStrategy, FontStrategy, LibraryStrategy};
@@ -1,6 +1,7 @@
 //! Path utilities.
 
-use std::path::{Path, PathBuf};
+use std::io;
+use std::path::{Component, Path, PathBuf};
 
 /// Normalize a file system path to absolute form.
 ///
@@ -18,3 +19,20 @@ pub fn normalize_path(path: &Path) -> PathBuf {
         }
     })
 }
+
+/// Join a root-relative file name onto `root`, keeping it inside the root.
+///
+/// Rejects absolute names and `..` components (e.g. user-submitted names
+/// of in-memory main files).
+pub(crate) fn join_in_root(root: &Path, name: &Path) -> io::Result<PathBuf> {
+    let escapes = name
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if escapes || name.as_os_str().is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("file name must be relative to the root: {}", name.display()),
+        ));
+    }
+    Ok(root.join(name))
+}
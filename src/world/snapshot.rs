@@ -39,6 +39,9 @@ pub struct SnapshotConfig {
     pub prelude: Option<String>,
     /// Code to inject at the end of each main file.
     pub postlude: Option<String>,
+    /// Pool to load files on (rayon's global pool if `None`).
+    #[cfg(feature = "batch")]
+    pub pool: Option<Arc<rayon::ThreadPool>>,
 }
 
 /// Immutable file content snapshot for lock-free parallel access.
@@ -88,7 +91,15 @@ impl FileSnapshot {
             .filter_map(|p| file_id_from_path(p, &root))
             .collect();
 
-        let sources = load_sources_with_imports(content_files, &root, config, &main_ids, on_load)?;
+        let on_load = &on_load;
+        let load = || load_sources_with_imports(content_files, &root, config, &main_ids, on_load);
+        #[cfg(feature = "batch")]
+        let sources = match &config.pool {
+            Some(pool) => pool.install(load)?,
+            None => load()?,
+        };
+        #[cfg(not(feature = "batch"))]
+        let sources = load()?;
 
         Ok(Self {
             sources: Arc::new(sources),
@@ -0,0 +1,94 @@
+//! File access statistics.
+//!
+//! Counters live on the `TypstWorld` (not thread-local), so accesses made
+//! from Typst's internal worker threads are counted too.
+
+use std::ops::AddAssign;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// File access statistics for a single compilation.
+///
+/// Which hit counter applies depends on the world's [`CacheStrategy`](super::CacheStrategy).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileStats {
+    /// Files loaded from disk or the virtual file system (cache misses).
+    ///
+    /// With the shared cache, this includes the once-per-compilation
+    /// re-read that validates a cached file by fingerprint.
+    pub files_read: u64,
+    /// Bytes loaded from disk or the virtual file system.
+    pub bytes_read: u64,
+    /// Hits in a pre-built [`FileSnapshot`](super::FileSnapshot).
+    pub snapshot_hits: u64,
+    /// Hits in the thread-local extension cache (snapshot mode).
+    pub thread_local_hits: u64,
+    /// Hits in a local, project or shared cache.
+    pub cache_hits: u64,
+    /// Lookups through `GLOBAL_FILE_CACHE`.
+    ///
+    /// Each lookup is also counted in `files_read` or `cache_hits`.
+    pub shared_lookups: u64,
+}
+
+impl FileStats {
+    /// Total cache hits across all cache layers.
+    pub fn hits(&self) -> u64 {
+        self.snapshot_hits + self.thread_local_hits + self.cache_hits
+    }
+}
+
+impl AddAssign for FileStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.files_read += rhs.files_read;
+        self.bytes_read += rhs.bytes_read;
+        self.snapshot_hits += rhs.snapshot_hits;
+        self.thread_local_hits += rhs.thread_local_hits;
+        self.cache_hits += rhs.cache_hits;
+        self.shared_lookups += rhs.shared_lookups;
+    }
+}
+
+/// Lock-free counters backing [`FileStats`].
+#[derive(Debug, Default)]
+pub(crate) struct FileCounters {
+    files_read: AtomicU64,
+    bytes_read: AtomicU64,
+    snapshot_hits: AtomicU64,
+    thread_local_hits: AtomicU64,
+    cache_hits: AtomicU64,
+    shared_lookups: AtomicU64,
+}
+
+impl FileCounters {
+    pub fn read(&self, bytes: usize) {
+        self.files_read.fetch_add(1, Ordering::Relaxed);
+        self.bytes_read.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn snapshot_hit(&self) {
+        self.snapshot_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn thread_local_hit(&self) {
+        self.thread_local_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn shared_lookup(&self) {
+        self.shared_lookups.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> FileStats {
+        FileStats {
+            files_read: self.files_read.load(Ordering::Relaxed),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            snapshot_hits: self.snapshot_hits.load(Ordering::Relaxed),
+            thread_local_hits: self.thread_local_hits.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            shared_lookups: self.shared_lookups.load(Ordering::Relaxed),
+        }
+    }
+}
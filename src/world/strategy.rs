@@ -7,7 +7,9 @@ use typst::Library;
 
 use super::cache::LocalCache;
 use super::snapshot::FileSnapshot;
-use crate::resource::library::create_library_with_inputs;
+use crate::resource::library::{create_library_with_inputs, GLOBAL_LIBRARY, GLOBAL_PAGED_LIBRARY};
+#[cfg(feature = "pdf")]
+use crate::resource::library::create_paged_library_with_inputs;
 
 /// Cache strategy for file access.
 pub enum CacheStrategy {
@@ -17,6 +19,10 @@ pub enum CacheStrategy {
     Shared,
     /// Pre-built snapshot + thread-local extension.
     Snapshot(Arc<FileSnapshot>),
+    /// Long-lived cache owned by a project, shared across compilations.
+    ///
+    /// Entries stay valid until explicitly invalidated.
+    Project(Arc<LocalCache>),
 }
 
 impl CacheStrategy {
@@ -34,6 +40,11 @@ impl CacheStrategy {
     pub fn snapshot(snapshot: Arc<FileSnapshot>) -> Self {
         Self::Snapshot(snapshot)
     }
+
+    /// Creates a project cache strategy from a long-lived cache.
+    pub fn project(cache: Arc<LocalCache>) -> Self {
+        Self::Project(cache)
+    }
 }
 
 /// Font strategy for compilation.
@@ -45,11 +56,16 @@ pub enum FontStrategy {
     Shared,
 }
 
-/// Library strategy for sys.inputs.
-#[derive(Clone)]
+/// Library strategy for sys.inputs and export target.
+#[derive(Debug, Clone)]
 pub enum LibraryStrategy {
     /// Use global library (no sys.inputs).
     Global,
+    /// Use global paged library (HTML feature disabled, no sys.inputs).
+    ///
+    /// Available without the `pdf` feature, so matches on this enum
+    /// compile with any feature set.
+    GlobalPaged,
     /// Custom library with sys.inputs.
     Custom(LazyHash<Library>),
 }
@@ -59,4 +75,19 @@ impl LibraryStrategy {
     pub fn with_inputs(inputs: typst::foundations::Dict) -> Self {
         Self::Custom(create_library_with_inputs(inputs))
     }
+
+    /// Creates a custom paged library strategy with the given sys.inputs.
+    #[cfg(feature = "pdf")]
+    pub fn paged_with_inputs(inputs: typst::foundations::Dict) -> Self {
+        Self::Custom(create_paged_library_with_inputs(inputs))
+    }
+
+    /// Get the library this strategy resolves to.
+    pub(crate) fn library(&self) -> &LazyHash<Library> {
+        match self {
+            Self::Global => &GLOBAL_LIBRARY,
+            Self::GlobalPaged => &GLOBAL_PAGED_LIBRARY,
+            Self::Custom(lib) => lib,
+        }
+    }
 }